@@ -0,0 +1,106 @@
+//! Compares the broadphase of the collision checks with testing every bullet against every target.
+//! Run it with `cargo bench --bench broadphase`.
+
+use std::time::{Duration, Instant};
+
+use bevy::math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume};
+use bevy::prelude::{Entity, Vec2};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use thunder::game::arena::Arena;
+use thunder::game::broadphase::Broadphase;
+
+const TARGET_COUNTS: [usize; 2] = [50, 500];
+const BULLET_COUNTS: [usize; 4] = [1000, 2000, 4000, 8000];
+const TICKS: u32 = 20;
+
+fn random_point(arena: &Arena, rng: &mut StdRng) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(arena.left()..arena.right()),
+        rng.gen_range(arena.bottom()..arena.top()),
+    )
+}
+
+// Test every bullet against every target, as the hit checks did before the broadphase
+fn brute_force(bullets: &[BoundingCircle], targets: &[(Entity, Aabb2d)]) -> usize {
+    bullets
+        .iter()
+        .map(|bullet| {
+            targets
+                .iter()
+                .filter(|(_, target)| bullet.intersects(target))
+                .count()
+        })
+        .sum()
+}
+
+// Sort the targets into the grid, then test every bullet against the targets near it
+fn with_broadphase(
+    broadphase: &mut Broadphase,
+    bullets: &[BoundingCircle],
+    targets: &[(Entity, Aabb2d)],
+) -> usize {
+    broadphase.rebuild(targets.iter().copied());
+    bullets
+        .iter()
+        .map(|bullet| {
+            broadphase
+                .query(bullet.aabb_2d())
+                .filter(|(_, target)| bullet.intersects(target))
+                .count()
+        })
+        .sum()
+}
+
+fn time_ticks(mut tick: impl FnMut() -> usize) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..TICKS {
+        hits = tick();
+    }
+    (start.elapsed() / TICKS, hits)
+}
+
+fn main() {
+    let arena = Arena::default();
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut broadphase = Broadphase::default();
+    for target_count in TARGET_COUNTS {
+        let targets: Vec<(Entity, Aabb2d)> = (0..target_count)
+            .map(|index| {
+                let center = random_point(&arena, &mut rng);
+                (
+                    Entity::from_raw(index as u32),
+                    Aabb2d::new(center, Vec2::splat(15.0)),
+                )
+            })
+            .collect();
+        println!("{} targets, time per tick:", target_count);
+        for bullet_count in BULLET_COUNTS {
+            bench(&mut broadphase, &targets, bullet_count, &arena, &mut rng);
+        }
+    }
+}
+
+// Time both ways of checking the hits of `bullet_count` bullets spread over the arena
+fn bench(
+    broadphase: &mut Broadphase,
+    targets: &[(Entity, Aabb2d)],
+    bullet_count: usize,
+    arena: &Arena,
+    rng: &mut StdRng,
+) {
+    let bullets: Vec<BoundingCircle> = (0..bullet_count)
+        .map(|_| BoundingCircle::new(random_point(arena, rng), 10.0))
+        .collect();
+    let (brute_force_time, brute_force_hits) = time_ticks(|| brute_force(&bullets, targets));
+    let (broadphase_time, broadphase_hits) =
+        time_ticks(|| with_broadphase(broadphase, &bullets, targets));
+    assert_eq!(brute_force_hits, broadphase_hits);
+    println!(
+        "{:>5} bullets: every target {:>10.3?}, broadphase {:>10.3?} ({:.1}x faster)",
+        bullet_count,
+        brute_force_time,
+        broadphase_time,
+        brute_force_time.as_secs_f64() / broadphase_time.as_secs_f64(),
+    );
+}
@@ -0,0 +1,174 @@
+//! This is the screen that is displayed when the selected level can't be played,
+//! for example when its level file is missing or invalid.
+//! It tells the player what went wrong and leads back to the main menu.
+
+use bevy::prelude::*;
+
+use super::{despawn_screen, GameState, TEXT_COLOR};
+use crate::game::level_loader::LevelError;
+
+// This plugin manages the error screen, with a "Home" button
+pub fn error_screen_plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Error), error_screen_setup)
+        .add_systems(
+            Update,
+            (error_screen_action, button_system).run_if(in_state(GameState::Error)),
+        )
+        .add_systems(OnExit(GameState::Error), despawn_screen::<OnErrorScreen>);
+}
+
+// Tag component used to tag entities added on the error screen
+#[derive(Component)]
+struct OnErrorScreen;
+
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+
+#[derive(Component)]
+enum ErrorButtonAction {
+    MainMenu,
+}
+
+// This system handles changing all buttons color based on mouse interaction
+#[allow(clippy::type_complexity)]
+fn button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        *color = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        }
+    }
+}
+
+fn error_screen_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_error: Res<LevelError>,
+) {
+    let button_style = Style {
+        width: Val::Px(300.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_icon_style = Style {
+        width: Val::Px(30.0),
+        // This takes the icons out of the flexbox flow, to be positioned exactly
+        position_type: PositionType::Absolute,
+        // The icon will be close to the left border of the button
+        left: Val::Px(10.0),
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnErrorScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        max_width: Val::Percent(80.0),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Display the title of the screen
+                    parent.spawn(
+                        TextBundle::from_section(
+                            "Oops...",
+                            TextStyle {
+                                font_size: 60.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(50.0)),
+                            ..default()
+                        }),
+                    );
+
+                    // Display what went wrong
+                    parent.spawn(
+                        TextBundle::from_section(
+                            level_error.to_string(),
+                            TextStyle {
+                                font_size: 25.0,
+                                color: TEXT_COLOR,
+                                ..default()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+
+                    parent // Back to Main Menu
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            ErrorButtonAction::MainMenu,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/home.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style,
+                                image: UiImage::new(icon),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section("Home", button_text_style));
+                        });
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn error_screen_action(
+    interaction_query: Query<
+        (&Interaction, &ErrorButtonAction),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, error_button_action) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match error_button_action {
+                ErrorButtonAction::MainMenu => game_state.set(GameState::Menu),
+            }
+        }
+    }
+}
@@ -1,38 +1,78 @@
 //! This is the main game page of Thunder.
 
-mod config;
+pub mod arena;
+pub mod broadphase;
+pub mod choreography;
+pub mod collider;
+pub mod config;
+pub mod contact;
 pub mod esc_menu;
+pub mod formation;
 pub mod generator;
+pub mod graze;
+#[cfg(feature = "headless")]
+pub mod headless;
+pub mod invulnerability;
 pub mod laser;
+pub mod level_loader;
+pub mod lives;
+pub mod path;
+pub mod pattern;
+pub mod projectile;
+pub mod replay;
+pub mod wave;
 pub mod win_lose_screen;
 use self::laser::{
     check_for_laser_star_capture, check_for_laserray_hitting, clear_laser, setup_laser,
     shoot_laser, update_laserboard, Laser, LaserBoardUi,
 };
 
-use super::{despawn_screen, GameState, Level};
+use super::{despawn_screen, GameState, Level, SeedSetting};
 // use bevy::sprite::Material2d;
 // use crate::animes::{AnimationIndices, AnimationTimer};
-use bevy::{
-    math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume},
-    prelude::*,
-    sprite::MaterialMesh2dBundle,
-};
-use config::MovingMode;
+use arena::{setup_arena, Arena};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use broadphase::{update_broadphase, Broadphase};
+use choreography::{check_for_escaped_enemies, Choreography};
+use collider::Collider;
+use config::{LevelConfig, MovingMode};
+use contact::check_for_body_contact;
 use core::f32::consts::PI;
+use formation::{break_formations, FormationRole};
+use graze::{check_for_graze, update_grazeboard, GrazeBoardUi};
+use invulnerability::{blink_invulnerable, Invulnerability, PlayerDamaged};
 use laser::{add_laser_star, remove_laser_star};
+use level_loader::{
+    check_level_load_failure, load_level, reload_level, CurrentLevel, LevelConfigLoader,
+};
+use lives::{animate_death_effect, lose_life, setup_lives, update_livesboard, LivesBoardUi};
+use path::FlightPath;
+use pattern::Emitter;
+use projectile::{
+    cull_bullets, setup_bullet_pool, setup_projectile_assets, setup_projectile_overlay,
+    update_projectile_overlay, BulletPool, MaxBullets, ProjectileAssets, ShowProjectileOverlay,
+    MAX_BULLETS,
+};
+use rand_core::SeedableRng;
+use replay::{
+    control_replay, read_player_input, run_replay_steps, save_recording, start_recording,
+    start_replay_from_setting, stop_replay, update_replay_hud, PlayerInput, ReplayPlayer,
+    SaveReplays,
+};
 use std::collections::VecDeque;
-// use bevy_rand::prelude::WyRand;
-// use bevy_rand::resource::GlobalEntropy;
-// use rand::Rng;
+use wave::WaveSchedule;
 
 //use super::{DisplayQuality, Volume};
 
 // These constants are defined in `Transform` units.
 // Using the default 2D camera they correspond 1:1 with screen pixels.
 const PLANE_SIZE: Vec3 = Vec3::new(30.0, 30.0, 0.0);
-const GAP_BETWEEN_PLANE_AND_WALL: f32 = 60.0;
 const PLAYER_PLANE_SPEED: f32 = 300.0;
+// While focusing, the player moves slower and sees the hitbox at the center of the plane
+const FOCUS_SPEED_FACTOR: f32 = 0.5;
+const PLAYER_HITBOX_RADIUS: f32 = 3.0;
+const HITBOX_DOT_COLOR: Color = Color::rgb(1.0, 0.1, 0.1);
 
 // How close can the plane get to the wall
 const PLANE_PADDING: f32 = 10.0;
@@ -46,18 +86,14 @@ const USER_BULLET_SPEED: f32 = 500.0;
 const DEFAULT_ENEMY_BULLET_DIRECTION: f32 = -PI / 2.0;
 
 const WALL_THICKNESS: f32 = 10.0;
-// x coordinates
-const RIGHT_WALL: f32 = 450.;
-const LEFT_WALL: f32 = -RIGHT_WALL;
-// y coordinates
-const TOP_WALL: f32 = 300.;
-const BOTTOM_WALL: f32 = -TOP_WALL;
-const PLANE_TOP_WALL_PADDING: f32 = GAP_BETWEEN_PLANE_AND_WALL * 3.0;
 
 const SCOREBOARD_FONT_SIZE: f32 = 40.0;
 const SCOREBOARD_TEXT_PADDING: Val = Val::Px(5.0);
 const HPBOARD_TEXT_PADDING: Val = Val::Px(50.0);
 const LASERBOARD_TEXT_PADDING: Val = Val::Px(95.0);
+const GRAZEBOARD_TEXT_PADDING: Val = Val::Px(140.0);
+// The lives are shown on the line of the HP, to its right
+const LIVESBOARD_TEXT_PADDING: Val = Val::Px(220.0);
 const MENU_BUTTON_PADDING: Val = Val::Px(10.0);
 const PLAYER_PLANE_HP: u32 = 500;
 
@@ -69,6 +105,7 @@ const TEXT_COLOR: Color = Color::rgb(0.5, 0.5, 1.0);
 const SCORE_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
 const MENU_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 
+// The default damage of a bullet, and the damage of a laser ray on every laser attack
 pub const HARM_BULLET: u32 = 12;
 pub const HARM_LASER: u32 = 1;
 
@@ -79,28 +116,72 @@ const DEFAULT_BULLET_SPEED: f32 = 350.0;
 
 // This plugin will contain the game. It will focus on the state `GameState::Game`
 pub fn game_plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::Init), game_setup)
+    app.init_asset::<LevelConfig>()
+        .init_asset_loader::<LevelConfigLoader>()
+        .insert_resource(SaveReplays(true))
+        .insert_resource(MaxBullets(MAX_BULLETS))
+        .init_resource::<ShowProjectileOverlay>()
+        .init_resource::<Broadphase>()
+        .add_systems(
+            Startup,
+            (setup_projectile_assets, start_replay_from_setting),
+        )
+        .add_systems(OnEnter(GameState::LevelSplash), load_level)
+        .add_systems(
+            Update,
+            check_level_load_failure.run_if(in_state(GameState::LevelSplash)),
+        )
+        .add_systems(
+            OnEnter(GameState::Init),
+            (
+                (setup_arena, setup_lives, game_setup).chain(),
+                setup_laser,
+                (setup_bullet_pool, setup_projectile_overlay),
+                (seed_rng, start_recording).chain(),
+            ),
+        )
         .add_event::<HittingEvent>()
-        .add_systems(OnEnter(GameState::Game), setup_laser)
+        .add_event::<PlayerDamaged>()
         // Add our gameplay simulation systems to the fixed timestep schedule
         // which runs at 64 Hz by default
         .add_systems(
             FixedUpdate,
             (
+                read_player_input,
                 generate_enemy,
                 shoot_gun,
-                control_velocity,
-                apply_velocity,
+                (
+                    break_formations,
+                    control_velocity,
+                    apply_velocity,
+                    check_for_escaped_enemies,
+                )
+                    .chain(),
                 clear_laser,
                 move_player_plane,
+                show_hitbox_dot,
                 shoot_laser,
-                check_for_bullet_hitting,
-                check_for_laserray_hitting,
-                check_for_laser_star_capture,
+                (
+                    update_broadphase,
+                    check_for_bullet_hitting,
+                    check_for_body_contact,
+                    cull_bullets,
+                    check_for_graze,
+                    check_for_laserray_hitting,
+                    check_for_laser_star_capture,
+                )
+                    .chain(),
+                lose_life,
+                animate_death_effect,
+                blink_invulnerable,
                 play_hitting_sound,
-                update_scoreboard,
-                update_hpboard,
-                update_laserboard,
+                (
+                    update_scoreboard,
+                    update_hpboard,
+                    update_livesboard,
+                    update_laserboard,
+                    update_grazeboard,
+                ),
                 check_for_next_wave,
                 add_laser_star,
                 remove_laser_star,
@@ -111,41 +192,80 @@ pub fn game_plugin(app: &mut App) {
         )
         .add_systems(
             Update,
-            (button_system, game_menu_action, back_on_esc).run_if(in_state(GameState::Game)),
+            (
+                button_system,
+                game_menu_action,
+                back_on_esc,
+                reload_level,
+                update_projectile_overlay,
+            )
+                .run_if(in_state(GameState::Game)),
+        )
+        .add_systems(
+            Update,
+            (control_replay, run_replay_steps, update_replay_hud)
+                .chain()
+                .run_if(in_state(GameState::Game).and_then(resource_exists::<ReplayPlayer>)),
         )
         .add_systems(
             OnEnter(GameState::Menu),
-            (despawn_screen::<OnGameScreen>, restore_background),
+            (
+                despawn_screen::<OnGameScreen>,
+                restore_background,
+                (save_recording, stop_replay).chain(),
+            ),
         )
         .add_systems(
             OnEnter(GameState::Win),
-            (despawn_screen::<OnGameScreen>, restore_background),
+            (
+                despawn_screen::<OnGameScreen>,
+                restore_background,
+                (save_recording, stop_replay).chain(),
+            ),
         )
         .add_systems(
             OnEnter(GameState::Lose),
-            (despawn_screen::<OnGameScreen>, restore_background),
+            (
+                despawn_screen::<OnGameScreen>,
+                restore_background,
+                (save_recording, stop_replay).chain(),
+            ),
         )
         .add_systems(
             OnEnter(GameState::Completion),
-            (despawn_screen::<OnGameScreen>, restore_background),
-        );
+            (
+                despawn_screen::<OnGameScreen>,
+                restore_background,
+                (save_recording, stop_replay).chain(),
+            ),
+        )
+        // A replay whose level can't be loaded never gets to the end of its run
+        .add_systems(OnEnter(GameState::Error), stop_replay);
 }
 
 // Tag component used to tag entities added on the game screen
 #[derive(Component)]
-struct OnGameScreen;
+pub struct OnGameScreen;
+
+#[derive(Component)]
+pub struct Player;
 
+// Tag component used to tag the dot that shows the hitbox of the player
 #[derive(Component)]
-struct Player;
+pub struct HitboxDot;
 
 #[derive(Component)]
-struct Enemy;
+pub struct Enemy;
 
 #[derive(Resource, Deref, DerefMut)]
-struct EnemyGenerateTimer(Timer);
+pub struct EnemyGenerateTimer(pub Timer);
 
 #[derive(Resource, Deref, DerefMut)]
-struct Wave(u32);
+pub struct Wave(pub u32);
+
+// The seed of the random generator for the level being played
+#[derive(Resource)]
+pub struct LevelSeed(pub u64);
 
 const GAME_NORMAL_BUTTON: Color = Color::rgb(0.5, 0.5, 0.5); // Normal state: gray
 const GAME_HOVERED_BUTTON: Color = Color::rgb(0.6, 0.6, 0.6); // Hovered state: slightly lighter gray
@@ -195,19 +315,41 @@ fn game_menu_action(
     }
 }
 
+// Reseed the random generator at the start of every level, so that a level played
+// with the same seed and the same inputs gives identical results
+fn seed_rng(
+    mut commands: Commands,
+    seed_setting: Res<SeedSetting>,
+    level: Res<Level>,
+    replay_player: Option<Res<ReplayPlayer>>,
+) {
+    // A replay must use the seed it was recorded with
+    let seed = match replay_player {
+        Some(replay_player) => replay_player.seed(),
+        None => seed_setting.0.unwrap_or_else(rand::random),
+    };
+    info!("Level {} starts with seed {}", level.0, seed);
+    commands.insert_resource(GlobalEntropy::<WyRand>::seed_from_u64(seed));
+    commands.insert_resource(LevelSeed(seed));
+}
+
 // Add the game's entities to our world
+#[allow(clippy::too_many_arguments)]
 fn game_setup(
     mut commands: Commands,
     atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     mut game_state: ResMut<NextState<GameState>>,
-    // mut meshes: ResMut<Assets<Mesh>>,
-    // mut materials: ResMut<Assets<ColorMaterial>>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
     asset_server: Res<AssetServer>,
     level: Res<Level>,
+    arena: Res<Arena>,
 ) {
     commands.insert_resource(Scoreboard {
         // hp: PLAYER_PLANE_HP,
         score: 0,
+        graze: 0,
+        escaped: 0,
     });
     commands.insert_resource(ClearColor(BACKGROUND_COLOR));
     commands.insert_resource(EnemyGenerateTimer(Timer::from_seconds(
@@ -215,6 +357,7 @@ fn game_setup(
         TimerMode::Once,
     )));
     commands.insert_resource(Wave(0));
+    commands.insert_resource(WaveSchedule::default());
 
     // commands.spawn(Camera2dBundle::default());
 
@@ -222,9 +365,12 @@ fn game_setup(
     let hitting_sound = asset_server.load("sounds/bullet_hitting.ogg");
     commands.insert_resource(HittingSound(hitting_sound));
 
-    // Player Plane
+    // Player Plane, with its hitbox dot
     let user_plane = generator::gen_user_plane(atlas_layouts, asset_server, level.0);
-    commands.spawn(user_plane);
+    let hitbox_dot = generator::gen_hitbox_dot(&mut projectile_assets, &mut materials);
+    commands.spawn(user_plane).with_children(|parent| {
+        parent.spawn(hitbox_dot);
+    });
 
     // Scoreboard
     commands.spawn((
@@ -280,6 +426,33 @@ fn game_setup(
         OnGameScreen,
     ));
 
+    // Livesboard
+    commands.spawn((
+        LivesBoardUi,
+        TextBundle::from_sections([
+            TextSection::new(
+                "Lives: ",
+                TextStyle {
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font_size: SCOREBOARD_FONT_SIZE,
+                color: SCORE_COLOR,
+                ..default()
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: HPBOARD_TEXT_PADDING,
+            left: LIVESBOARD_TEXT_PADDING,
+            ..default()
+        }),
+        OnGameScreen,
+    ));
+
     // Laserboard
     commands.spawn((
         LaserBoardUi,
@@ -307,6 +480,33 @@ fn game_setup(
         OnGameScreen,
     ));
 
+    // Grazeboard
+    commands.spawn((
+        GrazeBoardUi,
+        TextBundle::from_sections([
+            TextSection::new(
+                "Graze: ",
+                TextStyle {
+                    font_size: SCOREBOARD_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ),
+            TextSection::from_style(TextStyle {
+                font_size: SCOREBOARD_FONT_SIZE,
+                color: SCORE_COLOR,
+                ..default()
+            }),
+        ])
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: GRAZEBOARD_TEXT_PADDING,
+            left: SCOREBOARD_TEXT_PADDING,
+            ..default()
+        }),
+        OnGameScreen,
+    ));
+
     // Buttion Style
     let game_button_style = Style {
         width: Val::Px(100.0),
@@ -360,59 +560,91 @@ fn game_setup(
         });
 
     // Walls
-    commands.spawn((WallBundle::new(WallLocation::Left), OnGameScreen));
-    commands.spawn((WallBundle::new(WallLocation::Right), OnGameScreen));
-    commands.spawn((WallBundle::new(WallLocation::Bottom), OnGameScreen));
-    commands.spawn((WallBundle::new(WallLocation::Top), OnGameScreen));
+    commands.spawn((WallBundle::new(WallLocation::Left, &arena), OnGameScreen));
+    commands.spawn((WallBundle::new(WallLocation::Right, &arena), OnGameScreen));
+    commands.spawn((WallBundle::new(WallLocation::Bottom, &arena), OnGameScreen));
+    commands.spawn((WallBundle::new(WallLocation::Top, &arena), OnGameScreen));
 
     // Start the game
     game_state.set(GameState::Game);
 }
 
 #[derive(Component)]
-struct Plane;
+pub struct Plane;
 
 #[derive(Component, Clone)]
 pub struct GatlingGun {
-    bullet_config: BulletConfig,
-    shoot_timer: Timer,
+    pub bullet_config: BulletConfig,
+    pub shoot_timer: Timer,
+    // The pattern each shot fires
+    pub emitter: Emitter,
 }
 
 #[derive(Clone, Copy)]
-struct BulletConfig {
-    color: Color,
-    relative_position: Vec3,
-    diameter: f32,
-    speed: f32,
-    direction: BulletDirection,
+pub struct BulletConfig {
+    pub color: Color,
+    pub relative_position: Vec3,
+    pub diameter: f32,
+    pub speed: f32,
+    pub direction: BulletDirection,
+    pub damage: u32,
 }
 
 #[derive(Clone, Copy)]
-enum BulletDirection {
+pub enum BulletDirection {
     Fix(f32),
     Trace,
 }
 
 #[derive(Component)]
-struct Bullet;
+pub struct Bullet;
 
 // Bullet will despawn when hitting the entity with the BulletTarget component
 #[derive(Component)]
-struct AttackTarget;
+pub struct AttackTarget;
+
+/// The side a plane, a bullet or a laser ray fights for.
+/// Bullets only hit the targets of the other faction, and targets without a faction, like walls,
+/// stop the bullets of every faction.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+impl Faction {
+    pub fn can_hit(self, target: Option<&Faction>) -> bool {
+        target != Some(&self)
+    }
+}
 
 /// HP will decrease when hitted with Bullet
 /// When HP is 0, the entity with HP component will be despawned
 #[derive(Component)]
-struct HP(u32);
+pub struct HP(pub u32);
+
+/// The HP a plane starts with, and gets back when it respawns
+#[derive(Component, Clone, Copy)]
+pub struct MaxHp(pub u32);
+
+/// How much HP a bullet or a laser ray takes away from the target it hits
+#[derive(Component, Clone, Copy)]
+pub struct Damage(pub u32);
 
 #[derive(Component, Deref, DerefMut, Clone, Copy)]
-struct Velocity(Vec2);
+pub struct Velocity(pub Vec2);
 
+/// How an enemy plane moves
 #[derive(Component)]
-struct VelocityController(VecDeque<MovingMode>, Timer);
+pub enum VelocityController {
+    // Constant velocities, each kept for its time, in a loop
+    Steps(VecDeque<MovingMode>, Timer),
+    // A smooth path
+    Path(FlightPath),
+}
 
 #[derive(Event)]
-enum HittingEvent {
+pub enum HittingEvent {
     HitWall,
     HitPlane,
     HitLaserStar,
@@ -427,7 +659,8 @@ struct WallBundle {
     // You can nest bundles inside of other bundles like this
     // Allowing you to compose their functionality
     sprite_bundle: SpriteSheetBundle,
-    collider: AttackTarget,
+    attack_target: AttackTarget,
+    collider: Collider,
 }
 
 /// Which side of the arena is this wall located on?
@@ -439,19 +672,19 @@ enum WallLocation {
 }
 
 impl WallLocation {
-    fn position(&self) -> Vec2 {
+    fn position(&self, arena: &Arena) -> Vec2 {
         match self {
-            WallLocation::Left => Vec2::new(LEFT_WALL, 0.),
-            WallLocation::Right => Vec2::new(RIGHT_WALL, 0.),
-            WallLocation::Bottom => Vec2::new(0., BOTTOM_WALL),
-            WallLocation::Top => Vec2::new(0., TOP_WALL),
+            WallLocation::Left => Vec2::new(arena.left(), 0.),
+            WallLocation::Right => Vec2::new(arena.right(), 0.),
+            WallLocation::Bottom => Vec2::new(0., arena.bottom()),
+            WallLocation::Top => Vec2::new(0., arena.top()),
         }
     }
 
-    fn size(&self) -> Vec2 {
-        let arena_height = TOP_WALL - BOTTOM_WALL;
-        let arena_width = RIGHT_WALL - LEFT_WALL;
-        // Make sure we haven't messed up our constants
+    fn size(&self, arena: &Arena) -> Vec2 {
+        let arena_height = arena.height;
+        let arena_width = arena.width;
+        // Make sure the level hasn't messed up the arena
         assert!(arena_height > 0.0);
         assert!(arena_width > 0.0);
 
@@ -469,17 +702,17 @@ impl WallLocation {
 impl WallBundle {
     // This "builder method" allows us to reuse logic across our wall entities,
     // making our code easier to read and less prone to bugs when we change the logic
-    fn new(location: WallLocation) -> WallBundle {
+    fn new(location: WallLocation, arena: &Arena) -> WallBundle {
         WallBundle {
             sprite_bundle: SpriteSheetBundle {
                 transform: Transform {
                     // We need to convert our Vec2 into a Vec3, by giving it a z-coordinate
                     // This is used to determine the order of our sprites
-                    translation: location.position().extend(0.0),
+                    translation: location.position(arena).extend(0.0),
                     // The z-scale of 2D objects must always be 1.0,
                     // or their ordering will be affected in surprising ways.
                     // See https://github.com/bevyengine/bevy/issues/4149
-                    scale: location.size().extend(1.0),
+                    scale: location.size(arena).extend(1.0),
                     ..default()
                 },
                 sprite: Sprite {
@@ -488,24 +721,30 @@ impl WallBundle {
                 },
                 ..default()
             },
-            collider: AttackTarget,
+            attack_target: AttackTarget,
+            collider: Collider::rectangle(location.size(arena)),
         }
     }
 }
 
 // This resource tracks the game's score
 #[derive(Resource)]
-struct Scoreboard {
+pub struct Scoreboard {
     // hp: u32,
-    score: u32,
+    pub score: u32,
+    // The number of enemy bullets that passed close to the player
+    pub graze: u32,
+    // The number of enemies that left the arena without being shot down
+    pub escaped: u32,
 }
 
 #[derive(Component)]
-struct ScoreboardUi;
+pub struct ScoreboardUi;
 
 #[derive(Component)]
-struct HpboardUi;
+pub struct HpboardUi;
 
+#[allow(clippy::too_many_arguments)]
 fn generate_enemy(
     mut commands: Commands,
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
@@ -513,57 +752,75 @@ fn generate_enemy(
     time: Res<Time>,
     mut timer: ResMut<EnemyGenerateTimer>,
     wave: ResMut<Wave>,
-    level: Res<Level>,
-    // rng: ResMut<GlobalEntropy<WyRand>>,
+    levels: Res<Assets<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+    arena: Res<Arena>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+    mut schedule: ResMut<WaveSchedule>,
+    hp_query: Query<&HP, With<Enemy>>,
 ) {
+    schedule.tick(time.delta_seconds());
     if timer.tick(time.delta()).just_finished() {
         timer.0.reset();
         timer.0.pause();
-        let vec = generator::gen_wave(&mut atlas_layouts, &asset_server, level.0, wave.0);
-        for plane in vec {
-            commands.spawn(plane);
+        let level_config = levels.get(&current_level.0).unwrap();
+        // The level file may have been reloaded with fewer waves
+        if let Some(wave_config) = level_config.waves.get(wave.0 as usize) {
+            schedule.start(wave.0, wave_config, &arena, &mut rng);
+        }
+    }
+    schedule.call_reinforcements(&hp_query, &arena, &mut rng);
+    // The enemies are built as they come, from the level file as it is then
+    for pending in schedule.take_due() {
+        let hp = pending.config.hp;
+        let position = pending
+            .position
+            .unwrap_or_else(|| pending.config.position.gen(&arena, &mut *rng));
+        let enemy = generator::gen_enemy(
+            &mut atlas_layouts,
+            &asset_server,
+            pending.config,
+            position,
+            pending.offset,
+            &arena,
+            &mut rng,
+        );
+        let mut plane = commands.spawn(enemy);
+        if let Some(formation_role) = pending.formation_role {
+            plane.insert(formation_role);
+        }
+        if pending.leads {
+            schedule.set_leader(plane.id(), hp);
         }
     }
 }
 
 fn move_player_plane(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_input: Res<PlayerInput>,
     mut query: Query<&mut Transform, With<Player>>,
     time: Res<Time>,
+    arena: Res<Arena>,
 ) {
     let mut plane_transform = query.single_mut();
-    let mut direction = Vec3::new(0.0, 0.0, 0.0);
-
-    if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
-        direction.x -= 1.0;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-        direction.x += 1.0;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
-        direction.y -= 1.0;
-    }
-
-    if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
-        direction.y += 1.0;
-    }
+    let direction = player_input.direction.extend(0.0);
+    let speed = match player_input.focus {
+        true => PLAYER_PLANE_SPEED * FOCUS_SPEED_FACTOR,
+        false => PLAYER_PLANE_SPEED,
+    };
     assert_eq!(plane_transform.translation.z, 0.0);
     // Calculate the new horizontal plane position based on player input
-    let new_plane_position =
-        plane_transform.translation + direction * PLAYER_PLANE_SPEED * time.delta_seconds();
+    let new_plane_position = plane_transform.translation + direction * speed * time.delta_seconds();
 
     // Update the plane position,
     // making sure it doesn't cause the plane to leave the arena
-    let left_bound = LEFT_WALL + WALL_THICKNESS / 2.0 + PLANE_SIZE.x / 2.0 + PLANE_PADDING;
-    let right_bound = RIGHT_WALL - WALL_THICKNESS / 2.0 - PLANE_SIZE.x / 2.0 - PLANE_PADDING;
-    let down_bound = BOTTOM_WALL + WALL_THICKNESS / 2.0 + PLANE_SIZE.x / 2.0 + PLANE_PADDING;
-    let up_bound = TOP_WALL
+    let left_bound = arena.left() + WALL_THICKNESS / 2.0 + PLANE_SIZE.x / 2.0 + PLANE_PADDING;
+    let right_bound = arena.right() - WALL_THICKNESS / 2.0 - PLANE_SIZE.x / 2.0 - PLANE_PADDING;
+    let down_bound = arena.bottom() + WALL_THICKNESS / 2.0 + PLANE_SIZE.x / 2.0 + PLANE_PADDING;
+    let up_bound = arena.top()
         - WALL_THICKNESS / 2.0
         - PLANE_SIZE.x / 2.0
         - PLANE_PADDING
-        - PLANE_TOP_WALL_PADDING;
+        - arena.player_top_padding;
 
     plane_transform.translation = new_plane_position.clamp(
         Vec3::new(left_bound, down_bound, 0.0),
@@ -571,13 +828,67 @@ fn move_player_plane(
     );
 }
 
-fn control_velocity(mut query: Query<(&mut Velocity, &mut VelocityController)>, time: Res<Time>) {
-    for (mut velocity, mut controller) in &mut query {
-        if controller.1.tick(time.delta()).finished() {
-            let mode = controller.0.pop_front().unwrap();
-            *velocity = mode.velocity;
-            controller.1 = Timer::from_seconds(mode.time, TimerMode::Once);
-            controller.0.push_back(mode);
+// The hitbox of the player is only shown while focusing
+fn show_hitbox_dot(
+    player_input: Res<PlayerInput>,
+    mut query: Query<&mut Visibility, With<HitboxDot>>,
+) {
+    for mut visibility in &mut query {
+        *visibility = match player_input.focus {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+}
+
+type Steering<'a> = (
+    &'a mut Velocity,
+    &'a mut VelocityController,
+    &'a Transform,
+    Option<&'a mut Choreography>,
+    Option<&'a FormationRole>,
+);
+
+fn control_velocity(
+    mut query: Query<Steering>,
+    player_query: Query<&Transform, With<Player>>,
+    time: Res<Time>,
+) {
+    let player_position = player_query
+        .get_single()
+        .ok()
+        .map(|transform| transform.translation.truncate());
+    for (mut velocity, mut controller, transform, choreography, formation_role) in &mut query {
+        // Planes flying in or out of the arena don't move on their own
+        if let Some(mut choreography) = choreography {
+            if let Some(steered) =
+                choreography.steer(transform.translation.truncate(), time.delta())
+            {
+                *velocity = Velocity(steered);
+                continue;
+            }
+        }
+        match controller.as_mut() {
+            VelocityController::Steps(modes, timer) => {
+                if timer.tick(time.delta()).finished() {
+                    let mode = modes.pop_front().unwrap();
+                    *velocity = Velocity(mode.velocity);
+                    *timer = Timer::from_seconds(mode.time, TimerMode::Once);
+                    modes.push_back(mode);
+                }
+            }
+            // The velocity is set so that `apply_velocity` puts the plane right on its path
+            VelocityController::Path(flight_path) => {
+                // The leader of a formation picks the target of a dive for all of its planes
+                let target = player_position.map(|player_position| {
+                    formation_role.map_or(player_position, |role| role.aim(player_position))
+                });
+                *velocity = Velocity(flight_path.velocity(
+                    transform.translation.truncate(),
+                    target,
+                    time.delta_seconds(),
+                ));
+            }
         }
     }
 }
@@ -609,35 +920,48 @@ fn update_hpboard(
     text.sections[1].value = hp.to_string();
 }
 
+#[allow(clippy::type_complexity)]
 fn shoot_gun(
     mut commands: Commands,
     time: Res<Time>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut bullet_pool: ResMut<BulletPool>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut enemy_gun_query: Query<(&mut GatlingGun, &Transform), (With<Enemy>, Without<Player>)>,
-    mut player_gun_query: Query<(&mut GatlingGun, &Transform), With<Player>>,
+    mut enemy_gun_query: Query<
+        (&mut GatlingGun, &Transform, &Faction),
+        (With<Enemy>, Without<Player>),
+    >,
+    mut player_gun_query: Query<(&mut GatlingGun, &Transform, &Faction), With<Player>>,
 ) {
     let player_plane_loc = player_gun_query.single().1.translation;
-    for (mut gun, gun_transform) in &mut enemy_gun_query {
-        if gun.shoot_timer.tick(time.delta()).just_finished() {
-            commands.spawn(generator::gen_bullet(
-                &mut meshes,
+    for (mut gun, gun_transform, faction) in &mut enemy_gun_query {
+        let shoot = gun.shoot_timer.tick(time.delta()).just_finished();
+        for offset in gun.emitter.tick(shoot, time.delta()) {
+            let bullet = generator::gen_bullet(
+                &mut projectile_assets,
                 &mut materials,
                 &gun,
+                *faction,
                 gun_transform.translation,
                 player_plane_loc,
-            ));
+                offset,
+            );
+            bullet_pool.spawn(&mut commands, bullet);
         }
     }
-    for (mut gun, gun_transform) in &mut player_gun_query {
-        if gun.shoot_timer.tick(time.delta()).just_finished() {
-            commands.spawn(generator::gen_bullet(
-                &mut meshes,
+    for (mut gun, gun_transform, faction) in &mut player_gun_query {
+        let shoot = gun.shoot_timer.tick(time.delta()).just_finished();
+        for offset in gun.emitter.tick(shoot, time.delta()) {
+            let bullet = generator::gen_bullet(
+                &mut projectile_assets,
                 &mut materials,
                 &gun,
+                *faction,
                 gun_transform.translation,
                 player_plane_loc,
-            ));
+                offset,
+            );
+            bullet_pool.spawn(&mut commands, bullet);
         }
     }
 }
@@ -662,61 +986,80 @@ fn restore_background(mut commands: Commands) {
     commands.insert_resource(ClearColor::default());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_for_bullet_hitting(
     mut commands: Commands,
     mut scoreboard: ResMut<Scoreboard>,
-    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
+    mut bullet_pool: ResMut<BulletPool>,
+    broadphase: Res<Broadphase>,
+    bullet_query: Query<(Entity, &Transform, &Collider, &Faction, &Damage), With<Bullet>>,
     mut attack_target_query: Query<
         (
-            Entity,
             &Transform,
+            &Collider,
             Option<&mut HP>,
             Option<&Player>,
-            Option<&Plane>,
+            Option<&Faction>,
+            Option<&mut Invulnerability>,
         ),
         With<AttackTarget>,
     >,
     mut hitting_events: EventWriter<HittingEvent>,
-    mut game_state: ResMut<NextState<GameState>>,
+    mut player_damaged_events: EventWriter<PlayerDamaged>,
 ) {
-    for (bullet_entity, bullet_transform) in bullet_query.iter() {
+    for (bullet_entity, bullet_transform, bullet_collider, bullet_faction, damage) in
+        bullet_query.iter()
+    {
         let mut despawn_bullet = false;
-        for (target_entity, transform, maybe_hp, maybe_player, maybe_plane) in
-            &mut attack_target_query
-        {
-            let bullet_shape = BoundingCircle::new(
-                bullet_transform.translation.truncate(),
-                BULLET_DIAMETER / 2.,
-            );
-            let bullet_target_shape = match maybe_plane {
-                Some(_) => {
-                    Aabb2d::new(transform.translation.truncate(), PLANE_SIZE.truncate() / 2.)
-                }
-                None => Aabb2d::new(
-                    transform.translation.truncate(),
-                    transform.scale.truncate() / 2.,
-                ),
+        let bullet_position = bullet_transform.translation.truncate();
+        // Only the targets near the bullet can be hit
+        for (target_entity, _) in broadphase.query(bullet_collider.aabb(bullet_position)) {
+            // Laser stars are in the broadphase too, and targets may have been shot down already
+            let Ok((
+                transform,
+                collider,
+                maybe_hp,
+                maybe_player,
+                maybe_faction,
+                maybe_invulnerability,
+            )) = attack_target_query.get_mut(target_entity)
+            else {
+                continue;
             };
+            // There is no friendly fire
+            if !bullet_faction.can_hit(maybe_faction) {
+                continue;
+            }
 
-            if bullet_shape.intersects(&bullet_target_shape) {
+            if bullet_collider.intersects(
+                bullet_position,
+                collider,
+                transform.translation.truncate(),
+            ) {
+                // Bullets go through a target that has just been hit
+                if let Some(mut invulnerability) = maybe_invulnerability {
+                    if invulnerability.is_active() {
+                        continue;
+                    }
+                    invulnerability.start();
+                }
                 // Sends a hitting event so that other systems can react to the hitting
                 despawn_bullet = true;
 
                 // Bricks should be despawned and increment the scoreboard on hitting
                 match maybe_hp {
                     Some(mut hp) => {
-                        hp.0 = hp.0.saturating_sub(HARM_BULLET);
-                        if hp.0 == 0 {
-                            commands.entity(target_entity).despawn();
-                            match maybe_player {
-                                Some(_) => {
-                                    // TODO: Defeat
-                                    game_state.set(GameState::Lose);
-                                }
-                                None => {
-                                    scoreboard.score += 1;
-                                }
-                            }
+                        hp.0 = hp.0.saturating_sub(damage.0);
+                        if maybe_player.is_some() {
+                            player_damaged_events.send(PlayerDamaged {
+                                damage: damage.0,
+                                hp: hp.0,
+                            });
+                        }
+                        // The player loses a life instead, in `lose_life`
+                        if hp.0 == 0 && maybe_player.is_none() {
+                            commands.entity(target_entity).despawn_recursive();
+                            scoreboard.score += 1;
                         }
                         // if maybe_player.is_some() {
                         //     scoreboard.hp = scoreboard.hp.saturating_sub(1);
@@ -736,7 +1079,7 @@ fn check_for_bullet_hitting(
             }
         }
         if despawn_bullet {
-            commands.entity(bullet_entity).despawn();
+            bullet_pool.recycle(&mut commands, bullet_entity);
         }
     }
 }
@@ -772,19 +1115,31 @@ fn play_hitting_sound(
     hitting_events.clear();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_for_next_wave(
     plane: Query<&Enemy>,
     mut timer: ResMut<EnemyGenerateTimer>,
     mut wave: ResMut<Wave>,
     mut game_state: ResMut<NextState<GameState>>,
     level: Res<Level>,
+    levels: Res<Assets<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+    mut schedule: ResMut<WaveSchedule>,
 ) {
-    if plane.iter().next().is_none() && timer.paused() {
-        info!("All enemies are destroyed. Next wave is coming.");
+    let level_config = levels.get(&current_level.0).unwrap();
+    let cleared = plane.iter().next().is_none() && timer.paused() && schedule.is_empty();
+    // The time limit only brings on a next wave, the last one has to be cleared
+    let timed_out = schedule.timed_out() && wave.0 + 1 < level_config.get_wave_len();
+    if cleared || timed_out {
+        match cleared {
+            true => info!("All enemies are destroyed. Next wave is coming."),
+            false => info!("Time is up. Next wave is coming."),
+        }
+        schedule.clear();
         wave.0 += 1;
 
         *timer = EnemyGenerateTimer(Timer::from_seconds(ENEMY_GEN_INTERVAL, TimerMode::Once));
-        if wave.0 >= config::WaveConfig::get_wave_len(level.0) {
+        if wave.0 >= level_config.get_wave_len() {
             if level.0 == 5 {
                 game_state.set(GameState::Completion);
             } else {
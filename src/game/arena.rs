@@ -0,0 +1,103 @@
+//! This is the arena of Thunder, the walled playfield the game takes place in.
+//! Its size is set by each level file, so that levels can be tall or wide,
+//! and the walls, the player bounds and the spawning areas all follow it.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::config::LevelConfig;
+use super::level_loader::CurrentLevel;
+use super::{PLANE_PADDING, PLANE_SIZE, WALL_THICKNESS};
+
+/// The playfield of the level being played, centered on the origin.
+/// Fields left out in a level file take their value from `Arena::default()`.
+#[derive(Resource, Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct Arena {
+    pub width: f32,
+    pub height: f32,
+    // How far from the walls the enemies and the laser stars appear
+    pub gap: f32,
+    // The upper part of the arena that the player can't enter
+    pub player_top_padding: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            width: 900.0,
+            height: 600.0,
+            gap: 60.0,
+            player_top_padding: 180.0,
+        }
+    }
+}
+
+impl Arena {
+    // x coordinates
+    pub fn left(&self) -> f32 {
+        -self.width / 2.0
+    }
+
+    pub fn right(&self) -> f32 {
+        self.width / 2.0
+    }
+
+    // y coordinates
+    pub fn bottom(&self) -> f32 {
+        -self.height / 2.0
+    }
+
+    pub fn top(&self) -> f32 {
+        self.height / 2.0
+    }
+
+    /// The x and y ranges where enemies appear by default: the upper quarter of the arena
+    pub fn enemy_area(&self) -> (Vec2, Vec2) {
+        (
+            Vec2::new(self.left() + self.gap, self.right() - self.gap),
+            Vec2::new(
+                (self.top() * 3.0 + self.bottom()) / 4.0,
+                self.top() - self.gap,
+            ),
+        )
+    }
+
+    /// Whether the player plane, the enemies and the laser stars all have room in the arena
+    pub fn is_playable(&self) -> bool {
+        // The room the player plane takes up between two walls
+        let plane_room = WALL_THICKNESS + PLANE_SIZE.x + 2.0 * PLANE_PADDING;
+        let (enemy_x, enemy_y) = self.enemy_area();
+        let (star_x, star_y) = self.laser_star_area();
+        // Written so that a NaN in the level file doesn't pass either
+        self.width >= plane_room
+            && self.height - self.player_top_padding >= plane_room
+            && [enemy_x, enemy_y, star_x, star_y]
+                .iter()
+                .all(|range| range.x < range.y)
+    }
+
+    /// The x and y ranges where laser stars appear: anywhere the player can reach
+    pub fn laser_star_area(&self) -> (Vec2, Vec2) {
+        (
+            Vec2::new(self.left() + self.gap, self.right() - self.gap),
+            Vec2::new(
+                self.bottom() + self.gap,
+                self.top() - self.gap - self.player_top_padding,
+            ),
+        )
+    }
+}
+
+// Use the arena of the level that is about to be played
+pub(super) fn setup_arena(
+    mut commands: Commands,
+    levels: Res<Assets<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+) {
+    let arena = levels
+        .get(&current_level.0)
+        .map(|level_config| level_config.arena)
+        .unwrap_or_default();
+    commands.insert_resource(arena);
+}
@@ -0,0 +1,140 @@
+//! This is the broadphase of the collision checks of Thunder.
+//! The attack targets and the laser stars are sorted into a uniform grid once per fixed tick,
+//! so that a bullet, a laser ray or the player only has to be tested against what is near it,
+//! instead of against every target of the arena.
+
+use bevy::{math::bounding::Aabb2d, prelude::*};
+
+use super::collider::Collider;
+use super::laser::LaserStar;
+use super::AttackTarget;
+
+// The side of a grid cell, a few times the size of a plane
+pub const CELL_SIZE: f32 = 64.0;
+
+/// The shapes of the attack targets and the laser stars, sorted into the cells of a grid
+/// that covers all of them
+#[derive(Resource)]
+pub struct Broadphase {
+    cell_size: f32,
+    shapes: Vec<(Entity, Aabb2d)>,
+    // The cell of the lower left corner of each shape
+    first_cells: Vec<IVec2>,
+    // The cell of the lower left corner of the grid, and its number of columns and rows
+    origin: IVec2,
+    size: IVec2,
+    // The indices into `shapes` of the shapes that overlap each cell, row by row
+    cells: Vec<Vec<usize>>,
+}
+
+impl Default for Broadphase {
+    fn default() -> Self {
+        Broadphase::new(CELL_SIZE)
+    }
+}
+
+impl Broadphase {
+    pub fn new(cell_size: f32) -> Self {
+        Broadphase {
+            cell_size,
+            shapes: Vec::new(),
+            first_cells: Vec::new(),
+            origin: IVec2::ZERO,
+            size: IVec2::ZERO,
+            cells: Vec::new(),
+        }
+    }
+
+    /// Forget every shape and sort `shapes` into the grid instead
+    pub fn rebuild(&mut self, shapes: impl IntoIterator<Item = (Entity, Aabb2d)>) {
+        self.shapes.clear();
+        self.shapes.extend(shapes);
+        self.first_cells.clear();
+        // Keep the cells, and their allocations, as most of them are used again on the next tick
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+
+        let mut grid_min = IVec2::MAX;
+        let mut grid_max = IVec2::MIN;
+        for &(_, shape) in &self.shapes {
+            let (min, max) = self.cell_range(shape);
+            grid_min = grid_min.min(min);
+            grid_max = grid_max.max(max);
+            self.first_cells.push(min);
+        }
+        if self.shapes.is_empty() {
+            self.size = IVec2::ZERO;
+            return;
+        }
+        self.origin = grid_min;
+        self.size = grid_max - grid_min + IVec2::ONE;
+        let cell_count = (self.size.x * self.size.y) as usize;
+        if self.cells.len() < cell_count {
+            self.cells.resize_with(cell_count, Vec::new);
+        }
+
+        for (index, &(_, shape)) in self.shapes.iter().enumerate() {
+            let (min, max) = self.cell_range(shape);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let cell = self.cell_index(IVec2::new(x, y));
+                    self.cells[cell].push(index);
+                }
+            }
+        }
+    }
+
+    /// The shapes that share a cell with `area`, each once.
+    /// They may still miss `area`, so the caller makes the exact test.
+    pub fn query(&self, area: Aabb2d) -> impl Iterator<Item = (Entity, Aabb2d)> + '_ {
+        // Only the part of `area` that is on the grid can hold shapes
+        let (min, max) = self.cell_range(area);
+        let min = min.max(self.origin);
+        let max = max.min(self.origin + self.size - IVec2::ONE);
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .flat_map(move |cell| {
+                self.cells[self.cell_index(cell)]
+                    .iter()
+                    .filter_map(move |&index| {
+                        // A shape that spans several cells is found in each of them,
+                        // so it is only given for the first of them that `area` covers
+                        (self.first_cells[index].max(min) == cell).then_some(self.shapes[index])
+                    })
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    fn cell_range(&self, shape: Aabb2d) -> (IVec2, IVec2) {
+        (
+            (shape.min / self.cell_size).floor().as_ivec2(),
+            (shape.max / self.cell_size).floor().as_ivec2(),
+        )
+    }
+
+    fn cell_index(&self, cell: IVec2) -> usize {
+        let offset = cell - self.origin;
+        (offset.y * self.size.x + offset.x) as usize
+    }
+}
+
+// What can be hit or captured
+type InBroadphase = Or<(With<AttackTarget>, With<LaserStar>)>;
+
+// Sort the targets into the grid once they have all moved, before the hits are checked
+pub(super) fn update_broadphase(
+    mut broadphase: ResMut<Broadphase>,
+    target_query: Query<(Entity, &Transform, &Collider), InBroadphase>,
+) {
+    broadphase.rebuild(target_query.iter().map(|(entity, transform, collider)| {
+        (entity, collider.aabb(transform.translation.truncate()))
+    }));
+}
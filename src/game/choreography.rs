@@ -0,0 +1,208 @@
+//! This is the entry and exit of the enemy planes of Thunder.
+//! An enemy can fly in from outside the arena and settle at its position,
+//! and leave the arena again after a while.
+//! Enemies that are out of the arena once they have come in, whether they left on purpose
+//! or were carried out by their path, have escaped: they are taken out of the game
+//! without counting as shot down, so that they don't hold up the next wave.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::arena::Arena;
+use super::collider::Collider;
+use super::path::{FlightPath, PathConfig};
+use super::{Enemy, Scoreboard, WALL_THICKNESS};
+
+// How far past the walls the enemies that fly in appear, out of sight
+const OFF_ARENA_MARGIN: f32 = 60.0;
+
+/// A side of the arena that enemies come in or go out through
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ArenaSide {
+    #[default]
+    Top,
+    Left,
+    Right,
+    Bottom,
+}
+
+impl ArenaSide {
+    /// The direction that leads out of the arena through this side
+    pub fn direction(self) -> Vec2 {
+        match self {
+            ArenaSide::Top => Vec2::Y,
+            ArenaSide::Left => Vec2::NEG_X,
+            ArenaSide::Right => Vec2::X,
+            ArenaSide::Bottom => Vec2::NEG_Y,
+        }
+    }
+
+    /// The point past this side of the arena that is in line with `position`
+    pub fn outside(self, arena: &Arena, position: Vec2) -> Vec2 {
+        match self {
+            ArenaSide::Top => Vec2::new(position.x, arena.top() + OFF_ARENA_MARGIN),
+            ArenaSide::Left => Vec2::new(arena.left() - OFF_ARENA_MARGIN, position.y),
+            ArenaSide::Right => Vec2::new(arena.right() + OFF_ARENA_MARGIN, position.y),
+            ArenaSide::Bottom => Vec2::new(position.x, arena.bottom() - OFF_ARENA_MARGIN),
+        }
+    }
+}
+
+/// How an enemy flies in, as written in a level file.
+/// Fields left out take their value from `EntryConfig::default()`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EntryConfig {
+    pub side: ArenaSide,
+    // How long the enemy takes to reach its position
+    pub time: f32,
+}
+
+impl Default for EntryConfig {
+    fn default() -> Self {
+        EntryConfig {
+            side: ArenaSide::Top,
+            time: 1.5,
+        }
+    }
+}
+
+/// How an enemy leaves, as written in a level file.
+/// Fields left out take their value from `ExitConfig::default()`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ExitConfig {
+    // How long the enemy stays once it is in the arena
+    pub after: f32,
+    pub side: ArenaSide,
+    pub speed: f32,
+}
+
+impl Default for ExitConfig {
+    fn default() -> Self {
+        ExitConfig {
+            after: 10.0,
+            side: ArenaSide::Top,
+            speed: 200.0,
+        }
+    }
+}
+
+/// Where an enemy is in its flight in and out of the arena
+#[derive(Component, Clone, Debug)]
+pub enum Choreography {
+    // Flying in from outside the arena along `path`, which takes `time` seconds
+    Entering {
+        path: FlightPath,
+        time: f32,
+        exit: Option<ExitConfig>,
+    },
+    // Moving as its `VelocityController` says, until the timer to leave, if any, finishes
+    InArena {
+        exit: Option<(Timer, ExitConfig)>,
+    },
+    // Flying out of the arena
+    Leaving {
+        velocity: Vec2,
+    },
+}
+
+impl Choreography {
+    /// The choreography of an enemy whose position in the arena is `position`,
+    /// and where it appears
+    pub fn new(
+        arena: &Arena,
+        position: Vec2,
+        entry: Option<EntryConfig>,
+        exit: Option<ExitConfig>,
+    ) -> (Self, Vec2) {
+        match entry {
+            Some(entry) => {
+                let start = entry.side.outside(arena, position);
+                // The last control point is repeated, so that the plane slows down as it settles
+                let path = PathConfig::Bezier {
+                    points: vec![position - start, position - start],
+                    time: entry.time,
+                };
+                let choreography = Choreography::Entering {
+                    path: FlightPath::new(path, start),
+                    time: entry.time,
+                    exit,
+                };
+                (choreography, start)
+            }
+            None => (Choreography::in_arena(exit), position),
+        }
+    }
+
+    fn in_arena(exit: Option<ExitConfig>) -> Self {
+        Choreography::InArena {
+            exit: exit.map(|exit| (Timer::from_seconds(exit.after, TimerMode::Once), exit)),
+        }
+    }
+
+    /// Move the entry path by `offset`, for a plane that flies in beside another
+    pub fn shift(&mut self, offset: Vec2) {
+        if let Choreography::Entering { path, .. } = self {
+            path.start += offset;
+        }
+    }
+
+    pub fn is_entering(&self) -> bool {
+        matches!(self, Choreography::Entering { .. })
+    }
+
+    /// The velocity of a plane at `position` that is flying in or out of the arena,
+    /// or `None` when the plane moves as its `VelocityController` says
+    pub fn steer(&mut self, position: Vec2, delta: Duration) -> Option<Vec2> {
+        match self {
+            Choreography::Entering { path, time, exit } => {
+                let velocity = path.velocity(position, None, delta.as_secs_f32());
+                if path.elapsed >= *time {
+                    *self = Choreography::in_arena(*exit);
+                }
+                Some(velocity)
+            }
+            Choreography::InArena { exit } => {
+                let (timer, exit) = exit.as_mut()?;
+                if !timer.tick(delta).finished() {
+                    return None;
+                }
+                let velocity = exit.side.direction() * exit.speed;
+                *self = Choreography::Leaving { velocity };
+                Some(velocity)
+            }
+            Choreography::Leaving { velocity } => Some(*velocity),
+        }
+    }
+}
+
+// Enemies that are past the walls once they have come in have escaped
+pub(super) fn check_for_escaped_enemies(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    mut scoreboard: ResMut<Scoreboard>,
+    enemy_query: Query<(Entity, &Transform, &Collider, &Choreography), With<Enemy>>,
+) {
+    let bounds = Rect::new(
+        arena.left() - WALL_THICKNESS,
+        arena.bottom() - WALL_THICKNESS,
+        arena.right() + WALL_THICKNESS,
+        arena.top() + WALL_THICKNESS,
+    );
+    for (entity, transform, collider, choreography) in &enemy_query {
+        if choreography.is_entering() {
+            continue;
+        }
+        let aabb = collider.aabb(transform.translation.truncate());
+        if Rect::from_corners(aabb.min, aabb.max)
+            .intersect(bounds)
+            .is_empty()
+        {
+            commands.entity(entity).despawn_recursive();
+            scoreboard.escaped += 1;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! This is the collision shapes of Thunder.
+//! Every plane, bullet, laser ray, wall and laser star carries a `Collider`,
+//! and all the hit checks test those shapes, so that planes of any size get the hitbox they need.
+//! The shapes don't turn with their entity: boxes stay aligned with the axes and capsules stay upright.
+
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// The shape an entity is hit with, centered on its translation
+#[derive(Component, Clone, Debug, PartialEq, Deserialize)]
+pub enum Collider {
+    Circle { radius: f32 },
+    Aabb { half_size: Vec2 },
+    // An upright capsule: two half circles of `radius`, `2 * half_length` apart
+    Capsule { radius: f32, half_length: f32 },
+    // Several shapes, each moved by its offset from the center of the entity
+    Compound(Vec<(Vec2, Collider)>),
+}
+
+// Every simple shape is a box that may be rounded by a radius:
+// a circle is a point, and an upright capsule is a vertical segment, rounded by its radius.
+// Two of these shapes touch when their boxes are closer than the sum of their radii.
+#[derive(Clone, Copy)]
+struct RoundedBox {
+    min: Vec2,
+    max: Vec2,
+    radius: f32,
+}
+
+impl RoundedBox {
+    fn intersects(&self, other: &RoundedBox) -> bool {
+        let gap = (other.min - self.max)
+            .max(self.min - other.max)
+            .max(Vec2::ZERO);
+        let reach = self.radius + other.radius;
+        gap.length_squared() <= reach * reach
+    }
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Collider::Circle { radius }
+    }
+
+    /// A box of `size`
+    pub fn rectangle(size: Vec2) -> Self {
+        Collider::Aabb {
+            half_size: size / 2.,
+        }
+    }
+
+    pub fn capsule(radius: f32, half_length: f32) -> Self {
+        Collider::Capsule {
+            radius,
+            half_length,
+        }
+    }
+
+    /// The smallest box that holds the collider when it is centered on `position`
+    pub fn aabb(&self, position: Vec2) -> Aabb2d {
+        match self {
+            Collider::Circle { radius } => Aabb2d::new(position, Vec2::splat(*radius)),
+            Collider::Aabb { half_size } => Aabb2d::new(position, *half_size),
+            Collider::Capsule {
+                radius,
+                half_length,
+            } => Aabb2d::new(position, Vec2::new(*radius, half_length + radius)),
+            Collider::Compound(parts) => {
+                let mut aabb = Aabb2d {
+                    min: position,
+                    max: position,
+                };
+                for (offset, part) in parts {
+                    let part_aabb = part.aabb(position + *offset);
+                    aabb.min = aabb.min.min(part_aabb.min);
+                    aabb.max = aabb.max.max(part_aabb.max);
+                }
+                aabb
+            }
+        }
+    }
+
+    /// Whether the collider centered on `position` touches `other` centered on `other_position`
+    pub fn intersects(&self, position: Vec2, other: &Collider, other_position: Vec2) -> bool {
+        // Compound colliders touch what one of their parts touches
+        if let Collider::Compound(parts) = self {
+            return parts
+                .iter()
+                .any(|(offset, part)| part.intersects(position + *offset, other, other_position));
+        }
+        if let Collider::Compound(_) = other {
+            return other.intersects(other_position, self, position);
+        }
+        self.rounded_box(position)
+            .intersects(&other.rounded_box(other_position))
+    }
+
+    fn rounded_box(&self, position: Vec2) -> RoundedBox {
+        match self {
+            Collider::Circle { radius } => RoundedBox {
+                min: position,
+                max: position,
+                radius: *radius,
+            },
+            Collider::Aabb { half_size } => RoundedBox {
+                min: position - *half_size,
+                max: position + *half_size,
+                radius: 0.,
+            },
+            Collider::Capsule {
+                radius,
+                half_length,
+            } => RoundedBox {
+                min: position - Vec2::new(0., *half_length),
+                max: position + Vec2::new(0., *half_length),
+                radius: *radius,
+            },
+            Collider::Compound(_) => unreachable!("compound colliders are split into their parts"),
+        }
+    }
+}
@@ -1,54 +1,56 @@
 //! This is all the default settings for game.rs.
-//! It includes the default user plane, default enemy plane and default enemy generation plan.
+//! It includes the default user plane, default enemy plane and the types that describe
+//! the enemy generation plan of a level.
+//! The plans themselves live in `assets/levels/*.level.ron` and are loaded as `LevelConfig` assets.
 
 use std::collections::VecDeque;
 
 use bevy::math::Vec2;
+use bevy::reflect::TypePath;
 use bevy::render::color::Color;
-use rand::{thread_rng, Rng};
-
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::game::arena::Arena;
+use crate::game::choreography::{EntryConfig, ExitConfig};
+use crate::game::collider::Collider;
+use crate::game::formation::FormationConfig;
+use crate::game::lives::DEFAULT_LIVES;
+use crate::game::path::PathConfig;
+use crate::game::pattern::BulletPattern;
 use crate::game::*;
 
 const PI: f32 = std::f32::consts::PI;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default, Deserialize)]
 pub enum PositionConfig {
     #[allow(dead_code)]
     // Deterministic postion
     Determinate(Vec2),
     // Randomized position with range of x and y
     Random(Vec2, Vec2),
+    // Randomized position in the upper quarter of the arena
+    #[default]
+    Upper,
 }
 
 impl PositionConfig {
-    pub fn gen(self) -> Vec2 {
-        let mut rng = thread_rng();
+    pub fn gen(self, arena: &Arena, rng: &mut impl Rng) -> Vec2 {
         match self {
             Self::Determinate(position) => position,
             Self::Random(x_range, y_range) => Vec2::new(
                 rng.gen_range(x_range.x..x_range.y),
                 rng.gen_range(y_range.x..y_range.y),
             ),
+            Self::Upper => {
+                let (x_range, y_range) = arena.enemy_area();
+                Self::Random(x_range, y_range).gen(arena, rng)
+            }
         }
     }
 }
 
-impl Default for PositionConfig {
-    fn default() -> Self {
-        PositionConfig::Random(
-            Vec2::new(
-                LEFT_WALL + GAP_BETWEEN_PLANE_AND_WALL,
-                RIGHT_WALL - GAP_BETWEEN_PLANE_AND_WALL,
-            ),
-            Vec2::new(
-                (TOP_WALL * 3.0 + BOTTOM_WALL) / 4.0,
-                TOP_WALL - GAP_BETWEEN_PLANE_AND_WALL,
-            ),
-        )
-    }
-}
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
 pub enum BulletDirectionConfig {
     #[allow(dead_code)]
     Determinate(f32),
@@ -58,8 +60,7 @@ pub enum BulletDirectionConfig {
 }
 
 impl BulletDirectionConfig {
-    pub fn gen(self) -> BulletDirection {
-        let mut rng = thread_rng();
+    pub fn gen(self, rng: &mut impl Rng) -> BulletDirection {
         match self {
             Self::Determinate(angle) => BulletDirection::Fix(angle),
             Self::Random(angle_range) => {
@@ -76,10 +77,14 @@ impl Default for BulletDirectionConfig {
     }
 }
 
-#[derive(Clone)]
+// Fields left out in a level file take their value from `EnemyConfig::default()`
+#[derive(Clone, Deserialize)]
+#[serde(default)]
 pub struct EnemyConfig {
     pub position: PositionConfig,
     pub scale: Vec2,
+    // The hitbox of the enemy, a box of its `scale` when left out
+    pub collider: Option<Collider>,
     pub color: Color,
     pub hp: u32,
     pub bullet_color: Color,
@@ -87,29 +92,33 @@ pub struct EnemyConfig {
     pub bullet_speed: f32,
     pub bullet_direction: BulletDirectionConfig,
     pub bullet_diameter: f32,
+    pub bullet_damage: u32,
+    // What each shot fires, a single bullet when left out
+    pub bullet_pattern: BulletPattern,
+    // What the enemy takes from the player when they touch, nothing when left out
+    pub contact_damage: u32,
     pub shooting_interval: f32,
     pub moving_mode: VecDeque<MovingMode>,
+    // A smooth path to follow instead of the `moving_mode`
+    pub path: Option<PathConfig>,
+    // How the enemy flies in from outside the arena, it appears at its position when left out
+    pub entry: Option<EntryConfig>,
+    // When and how the enemy leaves the arena, it stays until shot down when left out
+    pub exit: Option<ExitConfig>,
+    // How many seconds after the start of its wave the enemy comes
+    pub spawn_delay: f32,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Deserialize)]
 pub struct MovingMode {
-    pub velocity: Velocity,
+    pub velocity: Vec2,
     pub time: f32,
 }
 
-impl MovingMode {
-    pub fn new(velocity_x: f32, velocity_y: f32, time: f32) -> Self {
-        MovingMode {
-            velocity: Velocity(Vec2::new(velocity_x, velocity_y)),
-            time,
-        }
-    }
-}
-
 impl Default for MovingMode {
     fn default() -> Self {
         MovingMode {
-            velocity: Velocity(Vec2::ZERO),
+            velocity: Vec2::ZERO,
             time: 10.0,
         }
     }
@@ -120,6 +129,7 @@ impl Default for EnemyConfig {
         EnemyConfig {
             position: PositionConfig::default(),
             scale: PLANE_SIZE.truncate(),
+            collider: None,
             color: PLANE_COLOR,
             hp: ENEMY_PLANE_HP,
             bullet_color: BULLET_COLOR,
@@ -127,973 +137,80 @@ impl Default for EnemyConfig {
             bullet_direction: BulletDirectionConfig::default(),
             bullet_relative_position: -BULLET_STARTING_RELATIVE_POSITION.truncate(),
             bullet_diameter: BULLET_DIAMETER,
+            bullet_damage: HARM_BULLET,
+            bullet_pattern: BulletPattern::Single,
+            contact_damage: 0,
             shooting_interval: BULLET_SHOOTING_INTERVAL,
             // default moving mode is no moving with speed = 0
             moving_mode: VecDeque::from([MovingMode::default()]),
+            path: None,
+            entry: None,
+            exit: None,
+            spawn_delay: 0.0,
         }
     }
 }
 
+#[derive(Clone, Deserialize)]
 pub enum WaveConfig {
-    #[allow(dead_code)]
-    Duplicate(EnemyConfig, u32),
+    // Boxed, as an enemy takes much more room than the other waves
+    Duplicate(Box<EnemyConfig>, u32),
     Detailed(Vec<EnemyConfig>),
+    Formation(Box<FormationConfig>),
+    // A wave with reinforcements or a time limit
+    Scripted(Box<ScriptedWaveConfig>),
 }
 
-impl WaveConfig {
-    pub fn get_wave_len(level: u32) -> u32 {
-        match level {
-            1 => 4,
-            2 => 4,
-            3 => 5,
-            4 => 5,
-            5 => 6,
-            _ => unimplemented!("level {} is unimplemented", level),
-        }
-    }
-    pub fn get(level: u32, wave: u32) -> WaveConfig {
-        match (level, wave) {
-            //level 1
-            (1, 0) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.5 * RIGHT_WALL,
-                        0.5 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 10,
-                    bullet_speed: 150.0,
-                    shooting_interval: 2.0,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.5 * LEFT_WALL,
-                        0.5 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 10,
-                    bullet_speed: 150.0,
-                    shooting_interval: 2.0,
-                    ..default()
-                },
-            ]),
-            (1, 1) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(0.0, 0.5 * TOP_WALL)),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 80,
-                    bullet_speed: 200.0,
-                    shooting_interval: 1.3,
-                    ..default()
-                },
-                1,
-            ),
-            (1, 2) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 30,
-                    bullet_speed: 200.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                1,
-            ),
-            (1, 3) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 40,
-                    bullet_speed: 240.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                2,
-            ),
+/// A wave whose enemies are joined by reinforcements as it goes on
+#[derive(Clone, Deserialize)]
+pub struct ScriptedWaveConfig {
+    // The enemies that come at the start of the wave
+    pub enemies: WaveConfig,
+    #[serde(default)]
+    pub reinforcements: Vec<ReinforcementConfig>,
+    // The seconds after which the next wave comes, even if some enemies are left
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+}
 
-            //level 2
-            (2, 0) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 100,
-                    bullet_speed: 250.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                1,
-            ),
-            (2, 1) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 100,
-                    bullet_speed: 300.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 100,
-                    bullet_speed: 300.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-            ]),
-            (2, 2) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 300.0,
-                    shooting_interval: 1.4,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 120,
-                    bullet_speed: 300.0,
-                    shooting_interval: 0.8,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 300.0,
-                    shooting_interval: 1.4,
-                    ..default()
-                },
-            ]),
-            (2, 3) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.95 * LEFT_WALL, 0.65 * LEFT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 40,
-                    shooting_interval: 1.2,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.55 * LEFT_WALL, 0.3 * LEFT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    shooting_interval: 1.2,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 120,
-                    shooting_interval: 0.8,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.3 * RIGHT_WALL, 0.55 * RIGHT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    shooting_interval: 1.2,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.65 * RIGHT_WALL, 0.95 * RIGHT_WALL),
-                        Vec2::new(0.4 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 40,
-                    shooting_interval: 1.2,
-                    ..default()
-                },
-            ]),
+/// Enemies that join a wave once `trigger` happens
+#[derive(Clone, Deserialize)]
+pub struct ReinforcementConfig {
+    pub trigger: ReinforcementTrigger,
+    pub enemies: WaveConfig,
+    // Whether the reinforcement is called off when the wave is cleared before it comes,
+    // instead of holding the wave open until it does
+    #[serde(default)]
+    pub cancel_on_clear: bool,
+}
 
-            //level 3
-            (3, 0) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 100,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 100,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 100,
-                    ..default()
-                },
-            ]),
-            (3, 1) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 100,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 100,
-                    ..default()
-                },
-            ]),
-            (3, 2) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    ..default()
-                },
-            ]),
-            (3, 3) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-            ]),
-            (3, 4) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(0.9 * RIGHT_WALL, 0.0)),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 20,
-                    bullet_speed: 100.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.72 * RIGHT_WALL,
-                        0.4 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 40,
-                    bullet_speed: 150.0,
-                    shooting_interval: 0.6,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.54 * RIGHT_WALL,
-                        0.6 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 200.0,
-                    shooting_interval: 0.8,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.38 * RIGHT_WALL,
-                        0.75 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 120,
-                    bullet_speed: 300.0,
-                    shooting_interval: 0.9,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.18 * RIGHT_WALL,
-                        0.85 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 160,
-                    bullet_speed: 350.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(0.0, 0.9 * TOP_WALL)),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    hp: 200,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.18 * LEFT_WALL,
-                        0.85 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 160,
-                    bullet_speed: 350.0,
-                    shooting_interval: 1.0,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.36 * LEFT_WALL,
-                        0.75 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 120,
-                    bullet_speed: 300.0,
-                    shooting_interval: 0.9,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.54 * LEFT_WALL,
-                        0.6 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 80,
-                    bullet_speed: 200.0,
-                    shooting_interval: 0.8,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(
-                        0.72 * LEFT_WALL,
-                        0.4 * TOP_WALL,
-                    )),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 40,
-                    bullet_speed: 150.0,
-                    shooting_interval: 0.6,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Determinate(Vec2::new(0.9 * LEFT_WALL, 0.0)),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 20,
-                    bullet_speed: 100.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-            ]),
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum ReinforcementTrigger {
+    // Seconds after the start of the wave
+    After(f32),
+    // When the leader of the wave, the leader of its formation or else its first enemy,
+    // has less than this part of its HP left, or is gone
+    LeaderHpBelow(f32),
+}
 
-            //level 4
-            (4, 0) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.4,
-                    hp: 100,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.4,
-                    hp: 100,
-                    ..default()
-                },
-            ]),
-            (4, 1) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 100,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    hp: 150,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.6,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 100,
-                    ..default()
-                },
-            ]),
-            (4, 2) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.7 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 80,
-                    moving_mode: VecDeque::from([
-                        MovingMode::new(0.1 * LEFT_WALL, 0.0, 1.0),
-                        MovingMode::new(0.0, 0.1 * LEFT_WALL, 1.0),
-                    ]),
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.6 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 120,
-                    moving_mode: VecDeque::from([
-                        MovingMode::new(0.1 * LEFT_WALL, 0.0, 1.0),
-                        MovingMode::new(0.0, 0.1 * LEFT_WALL, 1.0),
-                    ]),
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 120,
-                    moving_mode: VecDeque::from([
-                        MovingMode::new(0.1 * LEFT_WALL, 0.0, 1.0),
-                        MovingMode::new(0.0, 0.1 * LEFT_WALL, 1.0),
-                    ]),
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.6 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 120,
-                    moving_mode: VecDeque::from([
-                        MovingMode::new(0.1 * LEFT_WALL, 0.0, 1.0),
-                        MovingMode::new(0.0, 0.1 * LEFT_WALL, 1.0),
-                    ]),
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.7 * RIGHT_WALL, 0.9 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 120,
-                    moving_mode: VecDeque::from([
-                        MovingMode::new(0.1 * LEFT_WALL, 0.0, 1.0),
-                        MovingMode::new(0.0, 0.1 * LEFT_WALL, 1.0),
-                    ]),
-                    ..default()
-                },
-            ]),
-            (4, 3) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * RIGHT_WALL, 0.9 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.6 * RIGHT_WALL, 0.7 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.5 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.3 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.0, 0.1 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.1 * LEFT_WALL, 0.0),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.3 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.5 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.7 * LEFT_WALL, 0.6 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.9 * LEFT_WALL, 0.8 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Determinate(1.5 * PI),
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-            ]),
-            (4, 4) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.1 * LEFT_WALL, 0.1 * RIGHT_WALL),
-                        Vec2::new(0.7 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 1500,
-                    ..default()
-                },
-                1,
-            ),
+/// The enemy generation plan of a level, loaded from `assets/levels/level{n}.level.ron`
+#[derive(Asset, TypePath, Deserialize)]
+pub struct LevelConfig {
+    // The arena keeps its default size when it is left out
+    #[serde(default)]
+    pub arena: Arena,
+    // The lives of the player, `DEFAULT_LIVES` when it is left out
+    #[serde(default = "default_lives")]
+    pub lives: u32,
+    pub waves: Vec<WaveConfig>,
+}
 
-            // level 5
-            (5, 0) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 666.0,
-                    shooting_interval: 0.35,
-                    hp: 150,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 666.0,
-                    shooting_interval: 0.35,
-                    hp: 150,
-                    ..default()
-                },
-            ]),
-            (5, 1) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.9 * LEFT_WALL, 0.7 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 80,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.6 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 400.0,
-                    shooting_interval: 0.4,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * LEFT_WALL, 0.2 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.35,
-                    hp: 150,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.6 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.35,
-                    hp: 150,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.7 * RIGHT_WALL, 0.9 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.35,
-                    hp: 150,
-                    ..default()
-                },
-            ]),
-            (5, 2) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.5 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 450.0,
-                    shooting_interval: 0.4,
-                    hp: 1000,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * LEFT_WALL, 0.1 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.4,
-                    hp: 1000,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.1 * RIGHT_WALL, 0.4 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.4,
-                    hp: 1000,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.5 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 450.0,
-                    shooting_interval: 0.4,
-                    hp: 1000,
-                    ..default()
-                },
-            ]),
-            (5, 3) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.35,
-                    hp: 2200,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.8 * RIGHT_WALL),
-                        Vec2::new(0.2 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.35,
-                    hp: 2200,
-                    ..default()
-                },
-            ]),
-            (5, 4) => WaveConfig::Duplicate(
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.1 * LEFT_WALL, 0.1 * RIGHT_WALL),
-                        Vec2::new(0.7 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 666.0,
-                    shooting_interval: 0.2,
-                    hp: 5000,
-                    ..default()
-                },
-                1,
-            ),
-            (5, 5) => WaveConfig::Detailed(vec![
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.8 * RIGHT_WALL, 0.9 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.6 * RIGHT_WALL, 0.7 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.4 * RIGHT_WALL, 0.5 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.2 * RIGHT_WALL, 0.3 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.0, 0.1 * RIGHT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.1 * LEFT_WALL, 0.0),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.3 * LEFT_WALL, 0.2 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.5 * LEFT_WALL, 0.4 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.7 * LEFT_WALL, 0.6 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-                EnemyConfig {
-                    position: PositionConfig::Random(
-                        Vec2::new(0.9 * LEFT_WALL, 0.8 * LEFT_WALL),
-                        Vec2::new(0.5 * TOP_WALL, 0.8 * TOP_WALL),
-                    ),
-                    bullet_direction: BulletDirectionConfig::Trace,
-                    bullet_speed: 500.0,
-                    shooting_interval: 0.3,
-                    hp: 120,
-                    ..default()
-                },
-            ]),
-            _ => unimplemented!("Level {} wave {} is not implemented", level, wave),
-        }
+fn default_lives() -> u32 {
+    DEFAULT_LIVES
+}
+
+impl LevelConfig {
+    pub fn get_wave_len(&self) -> u32 {
+        self.waves.len() as u32
     }
 }
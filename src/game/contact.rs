@@ -0,0 +1,67 @@
+//! This is the body contact between the player and the enemy planes in Thunder.
+//! An enemy plane that overlaps the player hurts it like a bullet would, by the contact damage
+//! of its `EnemyConfig`, so that the player can't sit on top of the enemies,
+//! and enemies can be made to ram into the player.
+
+use bevy::prelude::*;
+
+use super::broadphase::Broadphase;
+use super::collider::Collider;
+use super::invulnerability::{Invulnerability, PlayerDamaged};
+use super::{Enemy, HittingEvent, Player, HP};
+
+/// How much HP the plane takes away from the player when it touches it
+#[derive(Component, Clone, Copy)]
+pub struct ContactDamage(pub u32);
+
+type PlayerBody<'a> = (
+    &'a Transform,
+    &'a Collider,
+    &'a mut HP,
+    &'a mut Invulnerability,
+);
+
+// The player is hurt by one enemy at most per hit, and then stays invulnerable for a while,
+// so that an enemy resting on the plane doesn't drain its HP on every tick
+pub(super) fn check_for_body_contact(
+    broadphase: Res<Broadphase>,
+    mut player_query: Query<PlayerBody, With<Player>>,
+    enemy_query: Query<(&Transform, &Collider, &ContactDamage), With<Enemy>>,
+    mut hitting_events: EventWriter<HittingEvent>,
+    mut player_damaged_events: EventWriter<PlayerDamaged>,
+) {
+    let Ok((player_transform, player_collider, mut hp, mut invulnerability)) =
+        player_query.get_single_mut()
+    else {
+        return;
+    };
+    // The plane may already have lost its last HP to a bullet on this tick
+    if invulnerability.is_active() || hp.0 == 0 {
+        return;
+    }
+    let player_position = player_transform.translation.truncate();
+    for (enemy_entity, _) in broadphase.query(player_collider.aabb(player_position)) {
+        let Ok((enemy_transform, enemy_collider, contact_damage)) = enemy_query.get(enemy_entity)
+        else {
+            continue;
+        };
+        // Passive enemies, like turrets, can be touched without harm
+        if contact_damage.0 == 0 {
+            continue;
+        }
+        if player_collider.intersects(
+            player_position,
+            enemy_collider,
+            enemy_transform.translation.truncate(),
+        ) {
+            invulnerability.start();
+            hp.0 = hp.0.saturating_sub(contact_damage.0);
+            player_damaged_events.send(PlayerDamaged {
+                damage: contact_damage.0,
+                hp: hp.0,
+            });
+            hitting_events.send(HittingEvent::HitPlane);
+            return;
+        }
+    }
+}
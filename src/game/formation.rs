@@ -0,0 +1,204 @@
+//! This is the formations of enemy planes in Thunder.
+//! A formation wave is a leader and the planes around it, laid out in a line, a V, a grid or a ring.
+//! All of them share the position, the path, the entry and the exit of the leader,
+//! each moved by its offset from the leader, so that the formation flies as one.
+//! When the leader is shot down the others either keep flying in formation or scatter.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use super::choreography::Choreography;
+use super::config::EnemyConfig;
+use super::path::{FlightPath, PathConfig};
+use super::VelocityController;
+
+// How far and how fast the planes of a formation scatter once their leader is gone
+const SCATTER_DISTANCE: f32 = 120.0;
+const SCATTER_SECONDS: f32 = 0.8;
+
+/// The layout of a formation, with the leader at the origin
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum FormationShape {
+    // A row, the leader in the middle
+    Line,
+    // Two rows going back from the leader at the tip
+    V,
+    // Rows of `columns` planes going back from the leader in the middle of the front row
+    Grid { columns: u32 },
+    // A circle around the leader
+    Ring,
+}
+
+impl FormationShape {
+    /// The offsets from the leader of the `count` planes of the formation, the leader's first.
+    /// `spacing` is the distance between neighbours, and the radius of a ring.
+    pub fn offsets(self, count: u32, spacing: f32) -> Vec<Vec2> {
+        if count == 0 {
+            return Vec::new();
+        }
+        match self {
+            FormationShape::Line => (0..count)
+                .map(|index| Vec2::new(alternate(index) * spacing, 0.0))
+                .collect(),
+            // The planes behind the leader are higher up, as the enemies fly down at the player
+            FormationShape::V => (0..count)
+                .map(|index| {
+                    let side = alternate(index);
+                    Vec2::new(side, side.abs()) * spacing
+                })
+                .collect(),
+            FormationShape::Grid { columns } => {
+                let columns = columns.max(1);
+                let leader_column = columns / 2;
+                let slot = |index: u32| {
+                    let (row, column) = (index / columns, index % columns);
+                    Vec2::new(column as f32 - leader_column as f32, row as f32) * spacing
+                };
+                // The leader takes the middle slot of the front row, and the others fill the rest
+                std::iter::once(Vec2::ZERO)
+                    .chain(
+                        (0..count.max(leader_column + 1))
+                            .filter(|index| *index != leader_column)
+                            .map(slot),
+                    )
+                    .take(count as usize)
+                    .collect()
+            }
+            FormationShape::Ring => {
+                let members = count - 1;
+                std::iter::once(Vec2::ZERO)
+                    .chain((0..members).map(|index| {
+                        // The first plane of the ring is right below the leader
+                        let angle = -TAU / 4.0 + TAU * index as f32 / members as f32;
+                        Vec2::from_angle(angle) * spacing
+                    }))
+                    .collect()
+            }
+        }
+    }
+}
+
+// 0, 1, -1, 2, -2, ...: the places of planes that spread out to both sides of the leader
+fn alternate(index: u32) -> f32 {
+    let step = index.div_ceil(2) as f32;
+    match index % 2 {
+        1 => step,
+        _ => -step,
+    }
+}
+
+/// What the planes of a formation do once their leader is gone
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum LeaderDeath {
+    // Keep flying in formation, without the leader
+    #[default]
+    Keep,
+    // Fly apart, then hold where they are
+    Scatter,
+}
+
+fn default_spacing() -> f32 {
+    60.0
+}
+
+/// A formation wave, as written in a level file
+#[derive(Clone, Deserialize)]
+pub struct FormationConfig {
+    // The leader, whose position, path, entry and exit the whole formation follows
+    pub leader: EnemyConfig,
+    // The other planes, like the leader when left out
+    #[serde(default)]
+    pub member: Option<EnemyConfig>,
+    pub shape: FormationShape,
+    // The number of planes, counting the leader
+    pub count: u32,
+    #[serde(default = "default_spacing")]
+    pub spacing: f32,
+    #[serde(default)]
+    pub on_leader_death: LeaderDeath,
+}
+
+impl FormationConfig {
+    /// The config of the planes besides the leader, flying the way the leader does
+    pub fn member_config(&self) -> EnemyConfig {
+        let mut member = self.member.clone().unwrap_or_else(|| self.leader.clone());
+        member.position = self.leader.position;
+        member.moving_mode = self.leader.moving_mode.clone();
+        member.path = self.leader.path.clone();
+        member.entry = self.leader.entry;
+        member.exit = self.leader.exit;
+        member.spawn_delay = self.leader.spawn_delay;
+        member
+    }
+}
+
+/// The place of a plane in a formation, which is known by the wave it came with
+#[derive(Component, Clone, Copy, Debug)]
+pub enum FormationRole {
+    Leader(u32),
+    Member {
+        formation: u32,
+        offset: Vec2,
+        on_leader_death: LeaderDeath,
+    },
+}
+
+impl FormationRole {
+    /// Where the plane aims when the leader aims at `target`,
+    /// so that a diving formation keeps its shape along the leader's path
+    pub fn aim(&self, target: Vec2) -> Vec2 {
+        match self {
+            FormationRole::Leader(_) => target,
+            FormationRole::Member { offset, .. } => target + *offset,
+        }
+    }
+}
+
+type FormationPlane<'a> = (
+    Entity,
+    &'a FormationRole,
+    &'a Transform,
+    &'a mut VelocityController,
+    Option<&'a Choreography>,
+);
+
+// The planes of a formation whose leader is gone are on their own from then on
+pub(super) fn break_formations(mut commands: Commands, mut query: Query<FormationPlane>) {
+    let leaders: Vec<u32> = query
+        .iter()
+        .filter_map(|(_, role, ..)| match role {
+            FormationRole::Leader(formation) => Some(*formation),
+            FormationRole::Member { .. } => None,
+        })
+        .collect();
+    for (entity, role, transform, mut controller, choreography) in &mut query {
+        let FormationRole::Member {
+            formation,
+            offset,
+            on_leader_death,
+        } = *role
+        else {
+            continue;
+        };
+        if leaders.contains(&formation) {
+            continue;
+        }
+        commands.entity(entity).remove::<FormationRole>();
+        // Planes still flying in finish their entry in formation
+        if on_leader_death == LeaderDeath::Keep
+            || choreography.is_some_and(Choreography::is_entering)
+        {
+            continue;
+        }
+        // Each plane flies away from where the leader was, and slows down to a stop
+        let away = offset.try_normalize().unwrap_or(Vec2::Y) * SCATTER_DISTANCE;
+        let path = PathConfig::Bezier {
+            points: vec![away, away],
+            time: SCATTER_SECONDS,
+        };
+        *controller =
+            VelocityController::Path(FlightPath::new(path, transform.translation.truncate()));
+    }
+}
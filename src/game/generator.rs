@@ -1,19 +1,29 @@
 use super::*;
 use crate::animes::setup_anime_periodical;
 use crate::animes::{AnimationIndices, AnimationTimer};
-use crate::game::config::{EnemyConfig, WaveConfig};
+use crate::game::arena::Arena;
+use crate::game::choreography::Choreography;
+use crate::game::collider::Collider;
+use crate::game::config::EnemyConfig;
+use crate::game::contact::ContactDamage;
+use crate::game::invulnerability::Invulnerability;
+use crate::game::path::FlightPath;
+use crate::game::pattern::Emitter;
+use crate::game::projectile::ProjectileAssets;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
 use core::f32::consts::PI;
 
-// use bevy_rand::prelude::GlobalEntropy;
-// use bevy_rand::prelude::WyRand;
-// use rand::{thread_rng, Rng};
-
 pub fn gen_user_plane(
     mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
     asset_server: Res<AssetServer>,
     level: u32,
 ) -> impl Bundle {
     // let plane_y = BOTTOM_WALL + GAP_BETWEEN_PLANE_AND_WALL;
+    let hp = match level {
+        1 => 100,
+        2 => 400,
+        _ => PLAYER_PLANE_HP,
+    };
     (
         Plane,
         setup_anime_periodical(
@@ -23,11 +33,8 @@ pub fn gen_user_plane(
             "textures/entities/player.png".to_string(),
             8,
         ),
-        HP(match level {
-            1 => 100,
-            2 => 400,
-            _ => PLAYER_PLANE_HP,
-        }),
+        HP(hp),
+        MaxHp(hp),
         AnimationIndices { first: 0, last: 7 },
         AnimationTimer(Timer::from_seconds(0.2, TimerMode::Repeating)),
         OnGameScreen,
@@ -42,6 +49,7 @@ pub fn gen_user_plane(
                     _ => USER_BULLET_SPEED,
                 },
                 direction: BulletDirection::Fix(PI / 2.0),
+                damage: HARM_BULLET,
             },
             shoot_timer: Timer::from_seconds(
                 match level {
@@ -51,6 +59,7 @@ pub fn gen_user_plane(
                 },
                 TimerMode::Repeating,
             ),
+            emitter: Emitter::default(),
         },
         Laser {
             enabled: true,
@@ -63,104 +72,153 @@ pub fn gen_user_plane(
             )),
         },
         AttackTarget,
+        // Only a few pixels at the center of the plane can be hit
+        Collider::circle(PLAYER_HITBOX_RADIUS),
+        Invulnerability::default(),
         Player,
+        Faction::Player,
     )
 }
 
-pub fn gen_wave(
-    atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
-    asset_server: &Res<AssetServer>,
-    level: u32,
-    wave: u32,
-) -> Vec<impl Bundle> {
-    let config = WaveConfig::get(level, wave);
-    match config {
-        WaveConfig::Duplicate(enemy_config, enemy_num) => (0..enemy_num)
-            .map(|_| gen_enemy(atlas_layouts, asset_server, enemy_config.clone()))
-            .collect(),
+// The dot that shows the hitbox of the player, on top of the plane
+pub(super) fn gen_hitbox_dot(
+    projectile_assets: &mut ProjectileAssets,
+    materials: &mut Assets<ColorMaterial>,
+) -> impl Bundle {
+    (
+        MaterialMesh2dBundle {
+            mesh: projectile_assets.bullet_mesh(),
+            material: projectile_assets.material(materials, HITBOX_DOT_COLOR),
+            transform: Transform::from_xyz(0., 0., 2.)
+                .with_scale(Vec2::splat(PLAYER_HITBOX_RADIUS * 2.).extend(1.)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HitboxDot,
+    )
+}
 
-        WaveConfig::Detailed(enemy_configs) => enemy_configs
-            .into_iter()
-            .map(|enemy_config| gen_enemy(atlas_layouts, asset_server, enemy_config))
-            .collect(),
-    }
+/// An enemy plane, as it is spawned
+#[derive(Bundle)]
+pub struct EnemyBundle {
+    plane: Plane,
+    sprite: SpriteSheetBundle,
+    animation_indices: AnimationIndices,
+    animation_timer: AnimationTimer,
+    gun: GatlingGun,
+    laser: Laser,
+    attack_target: AttackTarget,
+    collider: Collider,
+    on_game_screen: OnGameScreen,
+    hp: HP,
+    contact_damage: ContactDamage,
+    enemy: Enemy,
+    faction: Faction,
+    velocity: Velocity,
+    velocity_controller: VelocityController,
+    choreography: Choreography,
 }
 
-fn gen_enemy(
+// An enemy that flies the way one settling at `position` would, moved by `offset`,
+// so that the planes of a formation keep their places
+pub(super) fn gen_enemy(
     atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
     asset_server: &Res<AssetServer>,
     enemy_config: EnemyConfig,
-    // mut _rng: &mut ResMut<GlobalEntropy<WyRand>>,
-) -> impl Bundle {
-    // let plane_x = rng
-    //     .gen_range(LEFT_WALL + GAP_BETWEEN_PLANE_AND_WALL..RIGHT_WALL - GAP_BETWEEN_PLANE_AND_WALL);
-    // let plane_y = TOP_WALL - GAP_BETWEEN_PLANE_AND_WALL;
-    (
-        Plane,
-        setup_anime_periodical(
+    position: Vec2,
+    offset: Vec2,
+    arena: &Arena,
+    rng: &mut GlobalEntropy<WyRand>,
+) -> EnemyBundle {
+    let (mut choreography, spawn_position) =
+        Choreography::new(arena, position, enemy_config.entry, enemy_config.exit);
+    choreography.shift(offset);
+    let spawn_position = spawn_position + offset;
+    let position = position + offset;
+    EnemyBundle {
+        plane: Plane,
+        sprite: setup_anime_periodical(
             atlas_layouts,
             asset_server,
-            enemy_config.position.gen(),
+            spawn_position,
             "textures/entities/enemy.png".to_string(),
             32,
         ),
-        AnimationIndices { first: 0, last: 31 },
-        AnimationTimer(Timer::from_seconds(0.2, TimerMode::Repeating)),
-        GatlingGun {
+        animation_indices: AnimationIndices { first: 0, last: 31 },
+        animation_timer: AnimationTimer(Timer::from_seconds(0.2, TimerMode::Repeating)),
+        gun: GatlingGun {
             bullet_config: BulletConfig {
                 color: enemy_config.color,
                 relative_position: enemy_config.bullet_relative_position.extend(0.0),
                 diameter: enemy_config.bullet_diameter,
                 speed: enemy_config.bullet_speed,
-                direction: enemy_config.bullet_direction.gen(),
+                direction: enemy_config.bullet_direction.gen(rng),
+                damage: enemy_config.bullet_damage,
             },
             shoot_timer: Timer::from_seconds(enemy_config.shooting_interval, TimerMode::Repeating),
+            emitter: Emitter::new(enemy_config.bullet_pattern),
         },
-        Laser {
+        laser: Laser {
             enabled: false,
             duration_timer: None,
         },
-        AttackTarget,
-        OnGameScreen,
-        HP(enemy_config.hp),
-        Enemy,
-        Velocity(Vec2::ZERO),
-        VelocityController(
-            enemy_config.moving_mode,
-            Timer::from_seconds(0.0, TimerMode::Once),
-        ),
-    )
+        attack_target: AttackTarget,
+        collider: enemy_config
+            .collider
+            .unwrap_or_else(|| Collider::rectangle(enemy_config.scale)),
+        on_game_screen: OnGameScreen,
+        hp: HP(enemy_config.hp),
+        contact_damage: ContactDamage(enemy_config.contact_damage),
+        enemy: Enemy,
+        faction: Faction::Enemy,
+        velocity: Velocity(Vec2::ZERO),
+        // The path starts where the plane settles once it is in the arena
+        velocity_controller: match enemy_config.path {
+            Some(path) => VelocityController::Path(FlightPath::new(path, position)),
+            None => VelocityController::Steps(
+                enemy_config.moving_mode,
+                Timer::from_seconds(0.0, TimerMode::Once),
+            ),
+        },
+        choreography,
+    }
 }
 
 pub fn gen_bullet(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
+    projectile_assets: &mut ProjectileAssets,
+    materials: &mut Assets<ColorMaterial>,
     gun: &GatlingGun,
+    faction: Faction,
     weapon_location: Vec3,
     player_plane_location: Vec3,
+    // The angle between the bullet and the direction of the gun, for the bullets of a pattern
+    offset: f32,
 ) -> impl Bundle {
     let bullet_position = weapon_location + gun.bullet_config.relative_position;
     return (
         MaterialMesh2dBundle {
-            mesh: meshes.add(Circle::default()).into(),
-            material: materials.add(gun.bullet_config.color).into(),
+            mesh: projectile_assets.bullet_mesh(),
+            material: projectile_assets.material(materials, gun.bullet_config.color),
             transform: Transform::from_translation(bullet_position)
                 .with_scale(Vec2::splat(gun.bullet_config.diameter).extend(1.)),
             ..default()
         },
         match gun.bullet_config.direction {
             BulletDirection::Fix(angle) => {
-                Velocity(Vec2::from_angle(angle) * gun.bullet_config.speed)
+                Velocity(Vec2::from_angle(angle + offset) * gun.bullet_config.speed)
             }
             BulletDirection::Trace => {
                 let direction = (player_plane_location - bullet_position)
                     .truncate()
                     .try_normalize()
                     .unwrap_or(Vec2::from_angle(DEFAULT_ENEMY_BULLET_DIRECTION));
-                Velocity(direction * gun.bullet_config.speed)
+                Velocity(Vec2::from_angle(offset).rotate(direction) * gun.bullet_config.speed)
             }
         },
         Bullet,
+        Collider::circle(gun.bullet_config.diameter / 2.),
+        faction,
+        Damage(gun.bullet_config.damage),
         OnGameScreen,
     );
 }
@@ -0,0 +1,100 @@
+//! This is the graze mechanic of Thunder.
+//! An enemy bullet that passes close to the player without hitting it is a graze:
+//! it counts once per bullet on the scoreboard, and recharges the laser a little.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::collider::Collider;
+use super::laser::Laser;
+use super::{Bullet, Faction, Player, Scoreboard};
+
+// How far from the center of the player a bullet grazes it
+pub const GRAZE_RADIUS: f32 = 30.0;
+// How many seconds of laser each graze gives back, up to the full duration of the laser
+pub const GRAZE_LASER_RECHARGE: f32 = 0.2;
+
+/// Where a bullet is in its graze of the player
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Graze {
+    // The bullet is inside the graze radius
+    Passing,
+    // The bullet has left the graze radius without hitting, and has been counted
+    Counted,
+}
+
+#[derive(Component)]
+pub struct GrazeBoardUi;
+
+type BulletGraze<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Collider,
+    &'a Faction,
+    Option<&'a Graze>,
+);
+
+// Bullets that hit the player are taken out of the game before they can leave the graze radius,
+// so only the bullets that pass by are counted
+pub(super) fn check_for_graze(
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut player_query: Query<(&Transform, &Faction, &mut Laser), With<Player>>,
+    bullet_query: Query<BulletGraze, With<Bullet>>,
+) {
+    let Ok((player_transform, player_faction, mut laser)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_position = player_transform.translation.truncate();
+    let graze_zone = Collider::circle(GRAZE_RADIUS);
+    for (bullet_entity, bullet_transform, bullet_collider, bullet_faction, maybe_graze) in
+        &bullet_query
+    {
+        if !bullet_faction.can_hit(Some(player_faction)) || maybe_graze == Some(&Graze::Counted) {
+            continue;
+        }
+        let inside = bullet_collider.intersects(
+            bullet_transform.translation.truncate(),
+            &graze_zone,
+            player_position,
+        );
+        match (inside, maybe_graze) {
+            (true, None) => {
+                commands.entity(bullet_entity).insert(Graze::Passing);
+            }
+            (false, Some(Graze::Passing)) => {
+                commands.entity(bullet_entity).insert(Graze::Counted);
+                scoreboard.graze += 1;
+                recharge_laser(&mut laser);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn recharge_laser(laser: &mut Laser) {
+    if !laser.enabled {
+        return;
+    }
+    if let Some(timer) = laser.duration_timer.as_mut() {
+        // There is nothing to give back to an unused laser, or to a level without laser
+        if timer.elapsed().is_zero() {
+            return;
+        }
+        let elapsed = timer
+            .elapsed()
+            .saturating_sub(Duration::from_secs_f32(GRAZE_LASER_RECHARGE));
+        // A finished timer only starts again once it is reset
+        timer.reset();
+        timer.set_elapsed(elapsed);
+    }
+}
+
+pub(super) fn update_grazeboard(
+    scoreboard: Res<Scoreboard>,
+    mut query: Query<&mut Text, With<GrazeBoardUi>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[1].value = scoreboard.graze.to_string();
+}
@@ -0,0 +1,325 @@
+//! This is a headless harness that drives `game_plugin` under `MinimalPlugins`,
+//! without a window, a renderer or an audio device.
+//! The textures, meshes and sounds the game asks for are only registered as asset types,
+//! so the gameplay logic runs exactly as in the game and can be checked in `tests/`.
+
+use std::time::Duration;
+
+use bevy::{
+    asset::AssetPlugin, audio::AudioSource, input::ButtonInput, prelude::*,
+    time::TimeUpdateStrategy,
+};
+use bevy_rand::prelude::{EntropyPlugin, WyRand};
+
+use super::arena::Arena;
+use super::collider::Collider;
+use super::config::{LevelConfig, WaveConfig};
+use super::invulnerability::Invulnerability;
+use super::laser::{LaserStar, LASER_STAR_RADIUS};
+use super::level_loader::CurrentLevel;
+use super::lives::Lives;
+use super::projectile::MaxBullets;
+use super::replay::{InputRecorder, Replay, ReplayPlayer, SaveReplays};
+use super::{game_plugin, Bullet, Enemy, Player, Scoreboard, Wave, HP};
+use crate::{GameState, Level, ReplaySetting, SeedSetting};
+
+// How many frames to wait at most for the level file to load
+const MAX_LOADING_FRAMES: u32 = 1000;
+
+/// A game of a single level that is stepped one fixed tick at a time
+pub struct HeadlessGame {
+    app: App,
+}
+
+impl HeadlessGame {
+    /// Load `level` with the random generator seeded with `seed`, and stop right before its first tick
+    pub fn new(level: u32, seed: u64) -> Self {
+        Self::with_asset_folder("assets", level, seed)
+    }
+
+    /// Like `new`, with the level files and the other assets taken from `asset_folder`
+    pub fn with_asset_folder(asset_folder: &str, level: u32, seed: u64) -> Self {
+        Self::build(asset_folder, level, Some(seed)).start()
+    }
+
+    /// Play `replay` from the start of its level, with its inputs instead of the keyboard
+    pub fn replaying(replay: Replay) -> Self {
+        // Like in the game, the replay brings its own seed
+        let mut game = Self::build("assets", replay.level, None);
+        game.app.insert_resource(ReplayPlayer::new(replay));
+        game.start()
+    }
+
+    // The game before its level is loaded
+    fn build(asset_folder: &str, level: u32, seed: Option<u64>) -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin {
+                file_path: asset_folder.to_string(),
+                ..default()
+            },
+        ))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<AudioSource>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_state::<GameState>()
+        .insert_resource(Level(level))
+        .insert_resource(SeedSetting(seed))
+        .insert_resource(ReplaySetting(None))
+        .add_plugins((EntropyPlugin::<WyRand>::default(), game_plugin))
+        .insert_resource(SaveReplays(false))
+        // Every update advances the time by exactly one fixed tick
+        .insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+        app.finish();
+        app.cleanup();
+        HeadlessGame { app }
+    }
+
+    // Load the level, and stop right before its first tick
+    fn start(mut self) -> Self {
+        let game = &mut self;
+        game.set_state(GameState::LevelSplash);
+        for _ in 0..MAX_LOADING_FRAMES {
+            game.app.update();
+            if game.state() == GameState::Error || game.is_level_loaded() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        if game.state() == GameState::LevelSplash {
+            // The game itself starts on the next update, which also runs the first tick
+            game.set_state(GameState::Init);
+            game.app.update();
+        }
+        self
+    }
+
+    fn is_level_loaded(&self) -> bool {
+        let asset_server = self.app.world.resource::<AssetServer>();
+        self.app
+            .world
+            .get_resource::<CurrentLevel>()
+            .is_some_and(|current_level| current_level.is_loaded(asset_server))
+    }
+
+    fn set_state(&mut self, state: GameState) {
+        self.app
+            .world
+            .resource_mut::<NextState<GameState>>()
+            .set(state);
+    }
+
+    /// Hold `key` down from the next tick on
+    pub fn press(&mut self, key: KeyCode) {
+        self.app
+            .world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(key);
+    }
+
+    /// Let `key` go from the next tick on
+    pub fn release(&mut self, key: KeyCode) {
+        self.app
+            .world
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(key);
+    }
+
+    /// Allow at most `max_bullets` bullets in the game from the next tick on
+    pub fn set_max_bullets(&mut self, max_bullets: usize) {
+        self.app.insert_resource(MaxBullets(max_bullets));
+    }
+
+    /// Leave the player `lives` lives, counting the plane that is flying
+    pub fn set_lives(&mut self, lives: u32) {
+        self.app.insert_resource(Lives(lives));
+    }
+
+    /// Leave the player plane with `hp` HP, so that the next hit takes a life
+    pub fn set_player_hp(&mut self, hp: u32) {
+        self.app
+            .world
+            .query_filtered::<&mut HP, With<Player>>()
+            .single_mut(&mut self.app.world)
+            .0 = hp;
+    }
+
+    /// Play `waves` instead of the waves of the level file, as if the file had been reloaded
+    pub fn set_waves(&mut self, waves: Vec<WaveConfig>) {
+        let handle = self.app.world.resource::<CurrentLevel>().0.clone();
+        self.app
+            .world
+            .resource_mut::<Assets<LevelConfig>>()
+            .get_mut(&handle)
+            .unwrap()
+            .waves = waves;
+    }
+
+    /// Put the player plane at `position`, which the arena bounds it to on the next tick
+    pub fn move_player_to(&mut self, position: Vec2) {
+        let mut query = self
+            .app
+            .world
+            .query_filtered::<&mut Transform, With<Player>>();
+        for mut transform in query.iter_mut(&mut self.app.world) {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+
+    /// Put every enemy plane at `position`
+    pub fn move_enemies_to(&mut self, position: Vec2) {
+        let mut query = self
+            .app
+            .world
+            .query_filtered::<&mut Transform, With<Enemy>>();
+        for mut transform in query.iter_mut(&mut self.app.world) {
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+
+    /// Put a laser star at `position`
+    pub fn spawn_laser_star(&mut self, position: Vec2) {
+        self.app.world.spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            LaserStar,
+            Collider::circle(LASER_STAR_RADIUS),
+        ));
+    }
+
+    /// Leave the game for the main menu, as the player would from the game or the error screen
+    pub fn return_to_menu(&mut self) {
+        self.set_state(GameState::Menu);
+        self.app.update();
+    }
+
+    /// Run `ticks` fixed ticks of the game
+    pub fn step(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    /// The run recorded so far, which `replaying` plays again
+    pub fn recording(&self) -> Replay {
+        self.app.world.resource::<InputRecorder>().0.clone()
+    }
+
+    /// Whether the inputs come from a replay instead of the keyboard
+    pub fn is_replaying(&self) -> bool {
+        self.app.world.contains_resource::<ReplayPlayer>()
+    }
+
+    /// How fast the game runs, 0 when it is paused
+    pub fn playback_speed(&self) -> f32 {
+        let time = self.app.world.resource::<Time<Virtual>>();
+        match time.is_paused() {
+            true => 0.0,
+            false => time.relative_speed(),
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world.resource::<State<GameState>>().get()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world.resource::<Scoreboard>().score
+    }
+
+    /// The number of enemy bullets that passed close to the player
+    pub fn graze(&self) -> u32 {
+        self.app.world.resource::<Scoreboard>().graze
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.app.world.resource::<Lives>().0
+    }
+
+    /// The number of enemies that left the arena without being shot down
+    pub fn escaped(&self) -> u32 {
+        self.app.world.resource::<Scoreboard>().escaped
+    }
+
+    pub fn wave(&self) -> u32 {
+        self.app.world.resource::<Wave>().0
+    }
+
+    pub fn arena(&self) -> Arena {
+        *self.app.world.resource::<Arena>()
+    }
+
+    /// How many bullets are flying, not counting the pooled ones
+    pub fn bullet_count(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<Bullet>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    /// How many assets of type `A` are stored, to check that the game doesn't keep adding them
+    pub fn asset_count<A: Asset>(&self) -> usize {
+        self.app.world.resource::<Assets<A>>().len()
+    }
+
+    /// The HP of the player plane, or `None` once it has been shot down
+    pub fn player_hp(&mut self) -> Option<u32> {
+        self.app
+            .world
+            .query_filtered::<&HP, With<Player>>()
+            .get_single(&self.app.world)
+            .ok()
+            .map(|hp| hp.0)
+    }
+
+    /// Whether the player plane has just been hit and can't be hurt yet
+    pub fn player_invulnerable(&mut self) -> bool {
+        self.app
+            .world
+            .query_filtered::<&Invulnerability, With<Player>>()
+            .get_single(&self.app.world)
+            .is_ok_and(Invulnerability::is_active)
+    }
+
+    /// The position and HP of every enemy plane
+    pub fn enemies(&mut self) -> Vec<(Vec2, u32)> {
+        self.app
+            .world
+            .query_filtered::<(&Transform, &HP), With<Enemy>>()
+            .iter(&self.app.world)
+            .map(|(transform, hp)| (transform.translation.truncate(), hp.0))
+            .collect()
+    }
+
+    /// How many laser stars are waiting to be caught
+    pub fn laser_star_count(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<LaserStar>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    pub fn enemy_count(&mut self) -> usize {
+        self.app
+            .world
+            .query_filtered::<(), With<Enemy>>()
+            .iter(&self.app.world)
+            .count()
+    }
+
+    pub fn player_position(&mut self) -> Option<Vec2> {
+        self.app
+            .world
+            .query_filtered::<&Transform, With<Player>>()
+            .get_single(&self.app.world)
+            .ok()
+            .map(|transform| transform.translation.truncate())
+    }
+}
@@ -0,0 +1,65 @@
+//! This is the invulnerability of the player after being hit.
+//! For a short while after each hit the plane blinks and the bullets that reach it do no harm,
+//! so that bullets that overlap can't drain its HP all at once.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+// How long the player can't be hurt after a hit
+pub const INVULNERABILITY_SECONDS: f32 = 1.0;
+// How long the plane stays hidden, then shown, while it blinks
+const BLINK_INTERVAL: f32 = 0.1;
+
+/// The window after a hit during which the entity can't be hurt
+#[derive(Component)]
+pub struct Invulnerability(pub Timer);
+
+impl Default for Invulnerability {
+    // Not invulnerable until the first hit
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(INVULNERABILITY_SECONDS, TimerMode::Once);
+        timer.tick(timer.duration());
+        Invulnerability(timer)
+    }
+}
+
+impl Invulnerability {
+    pub fn is_active(&self) -> bool {
+        !self.0.finished()
+    }
+
+    pub fn start(&mut self) {
+        self.start_for(Duration::from_secs_f32(INVULNERABILITY_SECONDS));
+    }
+
+    /// Start a window of another length than after a hit, like after a respawn
+    pub fn start_for(&mut self, duration: Duration) {
+        self.0.set_duration(duration);
+        self.0.reset();
+    }
+}
+
+/// Sent every time the player loses HP
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlayerDamaged {
+    pub damage: u32,
+    // The HP left after the hit
+    pub hp: u32,
+}
+
+// Count down the invulnerability, and blink while it lasts
+pub(super) fn blink_invulnerable(
+    time: Res<Time>,
+    mut query: Query<(&mut Invulnerability, &mut Visibility)>,
+) {
+    for (mut invulnerability, mut visibility) in &mut query {
+        let timer = &mut invulnerability.0;
+        timer.tick(time.delta());
+        let blink = (timer.elapsed_secs() / BLINK_INTERVAL) as u32;
+        *visibility = match !timer.finished() && blink & 1 == 0 {
+            true => Visibility::Hidden,
+            false => Visibility::Inherited,
+        };
+    }
+}
@@ -1,8 +1,4 @@
-use bevy::{
-    math::bounding::{Aabb2d, BoundingCircle, IntersectsVolume},
-    prelude::*,
-    sprite::MaterialMesh2dBundle,
-};
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
 
 use super::*;
 // use super::{
@@ -10,31 +6,38 @@ use super::*;
 //     BOTTOM_WALL, GAP_BETWEEN_PLANE_AND_WALL, HARM_LASER, HP, LEFT_WALL, RIGHT_WALL, TOP_WALL,
 //     WALL_THICKNESS,
 // };
+use crate::game::arena::Arena;
+use crate::game::broadphase::Broadphase;
+use crate::game::collider::Collider;
 use crate::game::config::PositionConfig;
+use crate::game::projectile::ProjectileAssets;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
 
 use crate::Level;
 
 pub(super) const LASER_DURATION: f32 = 10.0;
 pub(super) const LASER_COLOR: Color = Color::rgba(1.0, 0.7, 0., 0.80);
 const LASER_STAR_SIZE: Vec3 = Vec3::new(1.5, 1.5, 0.);
+// How far from its center a laser star can be picked up
+pub(super) const LASER_STAR_RADIUS: f32 = 20.0;
 
 #[derive(Component)]
-pub(super) struct Laser {
-    pub(super) enabled: bool,
-    pub(super) duration_timer: Option<Timer>,
+pub struct Laser {
+    pub enabled: bool,
+    pub duration_timer: Option<Timer>,
 }
 
 #[derive(Component)]
-pub(super) struct LaserBoardUi;
+pub struct LaserBoardUi;
 
 #[derive(Component)]
-pub(super) struct LaserRay;
+pub struct LaserRay;
 
 #[derive(Resource)]
 pub(super) struct LaserAttackTimer(Timer);
 
 #[derive(Component)]
-pub(super) struct LaserStar;
+pub struct LaserStar;
 
 #[derive(Resource)]
 pub(super) struct LaserStarGenerateTimer(Timer);
@@ -62,23 +65,24 @@ pub(super) fn setup_laser(mut commands: Commands, level: Res<Level>) {
 }
 
 pub(super) fn shoot_laser(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    player_input: Res<PlayerInput>,
+    arena: Res<Arena>,
     mut commands: Commands,
     time: Res<Time>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut laser_query: Query<(&mut Laser, &Transform), With<Player>>,
+    mut laser_query: Query<(&mut Laser, &Transform, &Faction), With<Player>>,
 ) {
-    for (mut laser, transform) in &mut laser_query {
-        if laser.enabled
-            && !laser.duration_timer.as_mut().unwrap().finished()
-            && keyboard_input.pressed(KeyCode::KeyL)
+    for (mut laser, transform, faction) in &mut laser_query {
+        if laser.enabled && !laser.duration_timer.as_mut().unwrap().finished() && player_input.laser
         {
             laser.duration_timer.as_mut().unwrap().tick(time.delta());
             commands.spawn(gen_laserray(
-                &mut meshes,
+                &mut projectile_assets,
                 &mut materials,
+                *faction,
                 transform.translation,
+                &arena,
             ));
         }
     }
@@ -90,20 +94,16 @@ pub(super) fn clear_laser(mut commands: Commands, laser_query: Query<Entity, Wit
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn check_for_laserray_hitting(
     mut commands: Commands,
     mut scoreboard: ResMut<Scoreboard>,
     time: Res<Time>,
     mut laser_attack_timer: ResMut<LaserAttackTimer>,
-    mut laserray_query: Query<&Transform, With<LaserRay>>,
+    broadphase: Res<Broadphase>,
+    mut laserray_query: Query<(&Transform, &Collider, &Faction, &Damage), With<LaserRay>>,
     mut attack_target_query: Query<
-        (
-            Entity,
-            &Transform,
-            Option<&mut HP>,
-            Option<&Player>,
-            Option<&Plane>,
-        ),
+        (&Transform, &Collider, Option<&mut HP>, Option<&Faction>),
         With<AttackTarget>,
     >,
     mut hitting_events: EventWriter<HittingEvent>,
@@ -111,33 +111,27 @@ pub(super) fn check_for_laserray_hitting(
     if !laser_attack_timer.0.tick(time.delta()).just_finished() {
         return;
     }
-    for laserray_transform in &mut laserray_query {
-        for (target_entity, target_transform, maybe_hp, maybe_player, maybe_plane) in
-            &mut attack_target_query
-        {
-            if maybe_player.is_some() {
+    for (laserray_transform, laserray_collider, laserray_faction, damage) in &mut laserray_query {
+        let laserray_position = laserray_transform.translation.truncate();
+        for (target_entity, _) in broadphase.query(laserray_collider.aabb(laserray_position)) {
+            let Ok((target_transform, target_collider, maybe_hp, maybe_faction)) =
+                attack_target_query.get_mut(target_entity)
+            else {
                 continue;
-            }
-            let laserray_shape = Aabb2d::new(
-                laserray_transform.translation.truncate(),
-                laserray_transform.scale.truncate() / 2.,
-            );
-            let bullet_target_shape = match maybe_plane {
-                Some(_) => Aabb2d::new(
-                    target_transform.translation.truncate(),
-                    PLANE_SIZE.truncate() / 2.,
-                ),
-                None => Aabb2d::new(
-                    target_transform.translation.truncate(),
-                    target_transform.scale.truncate() / 2.,
-                ),
             };
+            if !laserray_faction.can_hit(maybe_faction) {
+                continue;
+            }
 
-            if laserray_shape.intersects(&bullet_target_shape) {
+            if laserray_collider.intersects(
+                laserray_position,
+                target_collider,
+                target_transform.translation.truncate(),
+            ) {
                 // Bricks should be despawned and increment the scoreboard on hitting
                 match maybe_hp {
                     Some(mut hp) => {
-                        hp.0 = hp.0.saturating_sub(HARM_LASER);
+                        hp.0 = hp.0.saturating_sub(damage.0);
                         if hp.0 <= 0 {
                             commands.entity(target_entity).despawn();
                             scoreboard.score += 1;
@@ -156,16 +150,18 @@ pub(super) fn check_for_laserray_hitting(
 }
 
 fn gen_laserray(
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<ColorMaterial>>,
+    projectile_assets: &mut ProjectileAssets,
+    materials: &mut Assets<ColorMaterial>,
+    faction: Faction,
     weapon_location: Vec3,
+    arena: &Arena,
 ) -> impl Bundle {
     let bullet_position = weapon_location;
-    let height = TOP_WALL - WALL_THICKNESS - bullet_position.y;
+    let height = arena.top() - WALL_THICKNESS - bullet_position.y;
     return (
         MaterialMesh2dBundle {
-            mesh: meshes.add(Rectangle::default()).into(),
-            material: materials.add(LASER_COLOR).into(),
+            mesh: projectile_assets.laserray_mesh(),
+            material: projectile_assets.material(materials, LASER_COLOR),
             transform: Transform::from_translation(
                 bullet_position + Vec3::new(0., height / 2. + 8., 0.),
             )
@@ -173,6 +169,9 @@ fn gen_laserray(
             ..default()
         },
         LaserRay,
+        Collider::rectangle(Vec2::new(20., height)),
+        faction,
+        Damage(HARM_LASER),
         OnGameScreen,
     );
 }
@@ -196,40 +195,40 @@ pub(super) fn update_laserboard(
     }
 }
 
-fn gen_laser_star(asset_server: Res<AssetServer>) -> impl Bundle {
+fn gen_laser_star(
+    asset_server: Res<AssetServer>,
+    arena: &Arena,
+    rng: &mut GlobalEntropy<WyRand>,
+) -> impl Bundle {
+    let (x_range, y_range) = arena.laser_star_area();
     (
         SpriteSheetBundle {
             texture: asset_server.load("textures/entities/star.fill.png"),
             transform: Transform {
-                translation: PositionConfig::Random(
-                    Vec2::new(
-                        LEFT_WALL + GAP_BETWEEN_PLANE_AND_WALL,
-                        RIGHT_WALL - GAP_BETWEEN_PLANE_AND_WALL,
-                    ),
-                    Vec2::new(
-                        BOTTOM_WALL + GAP_BETWEEN_PLANE_AND_WALL,
-                        TOP_WALL - GAP_BETWEEN_PLANE_AND_WALL - PLANE_TOP_WALL_PADDING,
-                    ),
-                )
-                .gen()
-                .extend(0.0),
+                translation: PositionConfig::Random(x_range, y_range)
+                    .gen(arena, rng)
+                    .extend(0.0),
                 scale: LASER_STAR_SIZE,
                 ..default()
             },
             ..default()
         },
         LaserStar,
+        Collider::circle(LASER_STAR_RADIUS),
         OnGameScreen,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) fn add_laser_star(
     asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
     mut commands: Commands,
     laser: Query<&Laser, With<Player>>,
     time: Res<Time>,
     mut laser_star_generate_timer: ResMut<LaserStarGenerateTimer>,
     mut laser_star_vanish_timer: ResMut<LaserStarVanishTimer>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
 ) {
     if laser.is_empty() {
         return;
@@ -244,7 +243,7 @@ pub(super) fn add_laser_star(
     {
         return;
     }
-    commands.spawn(gen_laser_star(asset_server));
+    commands.spawn(gen_laser_star(asset_server, &arena, &mut rng));
     laser_star_vanish_timer.0.reset();
 }
 
@@ -265,7 +264,8 @@ pub(super) fn remove_laser_star(
 pub(super) fn check_for_laser_star_capture(
     mut commands: Commands,
     mut hitting_events: EventWriter<HittingEvent>,
-    laser_star_query: Query<(Entity, &Transform), With<LaserStar>>,
+    broadphase: Res<Broadphase>,
+    laser_star_query: Query<(&Transform, &Collider), With<LaserStar>>,
     mut player_plane_query: Query<(&Transform, &mut Laser), With<Player>>,
 ) {
     if laser_star_query.is_empty() {
@@ -275,18 +275,28 @@ pub(super) fn check_for_laser_star_capture(
         info!("Player plane not found!!!!!!!!");
         return;
     }
-    let (laser_star_entity, laser_star_transform) = laser_star_query.single();
     let (player_transform, mut player_laser) = player_plane_query.single_mut();
-    let player_shape = Aabb2d::new(
-        player_transform.translation.truncate(),
-        PLANE_SIZE.truncate() / 2.,
-    );
+    let player_position = player_transform.translation.truncate();
+    // The stars are caught by the whole plane, not only by the hitbox that bullets hurt
+    let plane_body = Collider::rectangle(PLANE_SIZE.truncate());
 
-    let laser_star_shape = BoundingCircle::new(laser_star_transform.translation.truncate(), 20.0);
-    if player_shape.intersects(&laser_star_shape) {
-        commands.entity(laser_star_entity).despawn();
-        player_laser.enabled = true;
-        player_laser.duration_timer = Some(Timer::from_seconds(LASER_DURATION, TimerMode::Once));
-        hitting_events.send(HittingEvent::HitLaserStar);
+    // Only the laser stars near the player can be captured
+    for (laser_star_entity, _) in broadphase.query(plane_body.aabb(player_position)) {
+        let Ok((laser_star_transform, laser_star_collider)) =
+            laser_star_query.get(laser_star_entity)
+        else {
+            continue;
+        };
+        if plane_body.intersects(
+            player_position,
+            laser_star_collider,
+            laser_star_transform.translation.truncate(),
+        ) {
+            commands.entity(laser_star_entity).despawn();
+            player_laser.enabled = true;
+            player_laser.duration_timer =
+                Some(Timer::from_seconds(LASER_DURATION, TimerMode::Once));
+            hitting_events.send(HittingEvent::HitLaserStar);
+        }
     }
 }
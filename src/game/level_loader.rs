@@ -0,0 +1,168 @@
+//! This is the loader of the level files in `assets/levels`.
+//! Each level is a `LevelConfig` asset which is loaded when the level splash screen shows up.
+//! With the `hot-reload` feature the level files are watched, so that a level file edited during the game is reloaded.
+
+use bevy::{
+    asset::{
+        io::{AssetReaderError, Reader},
+        AssetLoadError, AssetLoadFailedEvent, AssetLoader, AsyncReadExt, LoadContext,
+    },
+    prelude::*,
+    utils::{thiserror, BoxedFuture},
+};
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use thiserror::Error;
+
+use super::arena::Arena;
+use super::config::LevelConfig;
+use super::wave::WaveSchedule;
+use super::Wave;
+use crate::{GameState, Level};
+
+#[derive(Default)]
+pub(super) struct LevelConfigLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub(super) enum LevelConfigLoaderError {
+    #[error("Could not load level file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse level file: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("The level has no waves")]
+    NoWaves,
+    #[error("The level gives the player no lives")]
+    NoLives,
+    #[error("The arena of the level is too small to play in")]
+    InvalidArena,
+}
+
+/// Why the selected level can't be played.
+/// It is inserted as a resource before entering `GameState::Error`.
+#[derive(Resource, Debug, Error)]
+pub enum LevelError {
+    #[error("Level {0} does not exist.")]
+    NotFound(u32),
+    #[error("Level {0} is invalid: {1}")]
+    Invalid(u32, String),
+}
+
+impl AssetLoader for LevelConfigLoader {
+    type Asset = LevelConfig;
+    type Settings = ();
+    type Error = LevelConfigLoaderError;
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let level_config = ron::de::from_bytes::<LevelConfig>(&bytes)?;
+            if level_config.waves.is_empty() {
+                return Err(LevelConfigLoaderError::NoWaves);
+            }
+            if level_config.lives == 0 {
+                return Err(LevelConfigLoaderError::NoLives);
+            }
+            if !level_config.arena.is_playable() {
+                return Err(LevelConfigLoaderError::InvalidArena);
+            }
+            Ok(level_config)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+// The handle of the level that is being played
+#[derive(Resource)]
+pub struct CurrentLevel(pub Handle<LevelConfig>);
+
+impl CurrentLevel {
+    pub fn is_loaded(&self, asset_server: &AssetServer) -> bool {
+        asset_server.is_loaded_with_dependencies(&self.0)
+    }
+}
+
+fn level_path(level: u32) -> String {
+    format!("levels/level{}.level.ron", level)
+}
+
+// Start loading the selected level, so that it is ready when the game begins
+pub fn load_level(mut commands: Commands, asset_server: Res<AssetServer>, level: Res<Level>) {
+    let handle = asset_server.load(level_path(level.0));
+    commands.insert_resource(CurrentLevel(handle));
+}
+
+// Leave the level splash screen for the error screen if the level file can't be loaded
+pub(super) fn check_level_load_failure(
+    mut commands: Commands,
+    mut failed_events: EventReader<AssetLoadFailedEvent<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+    level: Res<Level>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for event in failed_events.read() {
+        if event.id != current_level.0.id() {
+            continue;
+        }
+        let level_error = match &event.error {
+            AssetLoadError::AssetReaderError(AssetReaderError::NotFound(_)) => {
+                LevelError::NotFound(level.0)
+            }
+            error => LevelError::Invalid(level.0, error.to_string()),
+        };
+        error!("{}", level_error);
+        commands.insert_resource(level_error);
+        game_state.set(GameState::Error);
+    }
+}
+
+// The asset server has already replaced the `LevelConfig` when its file is modified.
+// The enemies in the arena are kept, while the enemies of the current wave that have not come yet
+// are planned again, and the waves that have not started yet will be generated from the new file
+// by `generate_enemy`.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn reload_level(
+    mut level_events: EventReader<AssetEvent<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelConfig>>,
+    level: Res<Level>,
+    wave: Res<Wave>,
+    mut schedule: ResMut<WaveSchedule>,
+    arena: Res<Arena>,
+    mut rng: ResMut<GlobalEntropy<WyRand>>,
+) {
+    for event in level_events.read() {
+        if !event.is_modified(&current_level.0) {
+            continue;
+        }
+        let Some(level_config) = levels.get(&current_level.0) else {
+            continue;
+        };
+        match schedule.wave() {
+            Some(current_wave) => {
+                schedule.replan(
+                    level_config.waves.get(current_wave as usize),
+                    &arena,
+                    &mut rng,
+                );
+                info!(
+                    "Level {} is reloaded. The rest of wave {} and the waves after it will use the new file.",
+                    level.0,
+                    current_wave + 1
+                );
+            }
+            None => info!(
+                "Level {} is reloaded. Waves from wave {} on will use the new file.",
+                level.0,
+                wave.0 + 1
+            ),
+        }
+    }
+}
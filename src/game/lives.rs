@@ -0,0 +1,139 @@
+//! This is the lives of the player in Thunder.
+//! Each level gives the player a number of lives. When the plane runs out of HP it loses a life:
+//! it goes down in a burst, then comes back at the bottom of the arena with full HP
+//! and a few seconds of invulnerability. The level is only lost with the last life.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use super::arena::Arena;
+use super::config::LevelConfig;
+use super::invulnerability::Invulnerability;
+use super::level_loader::CurrentLevel;
+use super::projectile::ProjectileAssets;
+use super::{MaxHp, OnGameScreen, Player, HP, PLANE_PADDING, PLANE_SIZE, WALL_THICKNESS};
+use crate::GameState;
+
+// The number of lives of a level that doesn't set it
+pub const DEFAULT_LIVES: u32 = 3;
+// How long the player can't be hurt after coming back
+pub const RESPAWN_INVULNERABILITY_SECONDS: f32 = 3.0;
+
+// The burst left where the plane went down
+const DEATH_EFFECT_COLOR: Color = Color::rgba(1.0, 0.5, 0.1, 0.8);
+const DEATH_EFFECT_SECONDS: f32 = 0.5;
+const DEATH_EFFECT_DIAMETER: f32 = 120.0;
+
+/// The lives left to the player, counting the plane that is flying
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Lives(pub u32);
+
+#[derive(Component)]
+pub struct LivesBoardUi;
+
+/// The burst of a plane going down, which grows until its timer finishes
+#[derive(Component)]
+pub struct DeathEffect(pub Timer);
+
+// Every level starts with the lives its file gives
+pub(super) fn setup_lives(
+    mut commands: Commands,
+    levels: Res<Assets<LevelConfig>>,
+    current_level: Res<CurrentLevel>,
+) {
+    let lives = levels
+        .get(&current_level.0)
+        .map_or(DEFAULT_LIVES, |level_config| level_config.lives);
+    commands.insert_resource(Lives(lives));
+}
+
+// A plane without HP loses a life, and comes back at the bottom of the arena if it has one left
+pub(super) fn lose_life(
+    mut commands: Commands,
+    mut lives: ResMut<Lives>,
+    arena: Res<Arena>,
+    mut projectile_assets: ResMut<ProjectileAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut HP,
+            &MaxHp,
+            &mut Invulnerability,
+        ),
+        With<Player>,
+    >,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (entity, mut transform, mut hp, max_hp, mut invulnerability) in &mut player_query {
+        if hp.0 > 0 {
+            continue;
+        }
+        commands.spawn(death_effect(
+            &mut projectile_assets,
+            &mut materials,
+            transform.translation.truncate(),
+        ));
+        lives.0 = lives.0.saturating_sub(1);
+        if lives.0 == 0 {
+            commands.entity(entity).despawn_recursive();
+            game_state.set(GameState::Lose);
+            continue;
+        }
+        hp.0 = max_hp.0;
+        transform.translation = respawn_position(&arena).extend(0.0);
+        invulnerability.start_for(Duration::from_secs_f32(RESPAWN_INVULNERABILITY_SECONDS));
+    }
+}
+
+// The middle of the lowest line the player can reach
+fn respawn_position(arena: &Arena) -> Vec2 {
+    Vec2::new(
+        0.0,
+        arena.bottom() + WALL_THICKNESS / 2.0 + PLANE_SIZE.y / 2.0 + PLANE_PADDING,
+    )
+}
+
+fn death_effect(
+    projectile_assets: &mut ProjectileAssets,
+    materials: &mut Assets<ColorMaterial>,
+    position: Vec2,
+) -> impl Bundle {
+    (
+        MaterialMesh2dBundle {
+            mesh: projectile_assets.bullet_mesh(),
+            material: projectile_assets.material(materials, DEATH_EFFECT_COLOR),
+            // Above the planes and the bullets
+            transform: Transform::from_translation(position.extend(3.0))
+                .with_scale(Vec3::new(0.0, 0.0, 1.0)),
+            ..default()
+        },
+        DeathEffect(Timer::from_seconds(DEATH_EFFECT_SECONDS, TimerMode::Once)),
+        OnGameScreen,
+    )
+}
+
+pub(super) fn animate_death_effect(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DeathEffect, &mut Transform)>,
+) {
+    for (entity, mut effect, mut transform) in &mut query {
+        if effect.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let diameter = DEATH_EFFECT_DIAMETER * effect.0.fraction();
+        transform.scale = Vec3::new(diameter, diameter, 1.0);
+    }
+}
+
+pub(super) fn update_livesboard(
+    lives: Res<Lives>,
+    mut query: Query<&mut Text, With<LivesBoardUi>>,
+) {
+    let mut text = query.single_mut();
+    text.sections[1].value = lives.0.to_string();
+}
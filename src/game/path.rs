@@ -0,0 +1,171 @@
+//! This is the flight paths of the enemy planes of Thunder.
+//! Instead of a loop of constant velocities, an enemy can follow a smooth path:
+//! a Bezier or Catmull-Rom spline, a sine strafe, a circle around an anchor,
+//! or a dive at the player that carries on out of the arena.
+//! The points of a path are offsets from where the plane appears,
+//! so that the same path can be given to enemies that appear at random positions.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// The shape of the path an enemy follows, as written in a level file
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum PathConfig {
+    // A Bezier curve from where the plane appears, with `points` as its other control points,
+    // flown in `time` seconds
+    Bezier {
+        points: Vec<Vec2>,
+        time: f32,
+    },
+    // A curve through where the plane appears and then each of `points`, flown in `time` seconds
+    CatmullRom {
+        points: Vec<Vec2>,
+        time: f32,
+    },
+    // A flight at `velocity` that sways `amplitude` to each side, once every `period` seconds
+    Sine {
+        velocity: Vec2,
+        amplitude: f32,
+        period: f32,
+    },
+    // A circle around the anchor at `center`, once every `period` seconds,
+    // counterclockwise, or clockwise with a negative period
+    Circle {
+        center: Vec2,
+        period: f32,
+    },
+    // Wait `delay` seconds, then fly at `speed` straight at where the player is, and on out of the arena
+    Dive {
+        delay: f32,
+        speed: f32,
+    },
+}
+
+/// A path that a plane follows, and how far along it the plane is
+#[derive(Clone, Debug)]
+pub struct FlightPath {
+    pub path: PathConfig,
+    // Where the plane was when it started the path
+    pub start: Vec2,
+    pub elapsed: f32,
+    // The direction of a dive, once it has started
+    heading: Option<Vec2>,
+}
+
+impl FlightPath {
+    pub fn new(path: PathConfig, start: Vec2) -> Self {
+        FlightPath {
+            path,
+            start,
+            elapsed: 0.0,
+            heading: None,
+        }
+    }
+
+    /// Where the path puts the plane `time` seconds after it started.
+    /// Splines stay at their last point once their time is over.
+    pub fn position_at(&self, time: f32) -> Vec2 {
+        match &self.path {
+            PathConfig::Bezier {
+                points,
+                time: total,
+            } => self.bezier(points, progress(time, *total)),
+            PathConfig::CatmullRom {
+                points,
+                time: total,
+            } => self.catmull_rom(points, progress(time, *total)),
+            PathConfig::Sine {
+                velocity,
+                amplitude,
+                period,
+            } => {
+                // The plane sways across its heading, or from side to side when it doesn't move
+                let side = velocity.try_normalize().map_or(Vec2::X, Vec2::perp);
+                self.start + *velocity * time + side * *amplitude * phase(time, *period).sin()
+            }
+            PathConfig::Circle { center, period } => {
+                let anchor = self.start + *center;
+                anchor + Vec2::from_angle(phase(time, *period)).rotate(-*center)
+            }
+            PathConfig::Dive { delay, speed } => match self.heading {
+                Some(heading) if time > *delay => self.start + heading * *speed * (time - delay),
+                _ => self.start,
+            },
+        }
+    }
+
+    /// The velocity that brings a plane at `position` onto the path by the end of a tick of `delta` seconds.
+    /// `target` is where a dive aims, which is the player unless the plane flies in a formation.
+    pub fn velocity(&mut self, position: Vec2, target: Option<Vec2>, delta: f32) -> Vec2 {
+        self.elapsed += delta;
+        if let PathConfig::Dive { delay, .. } = self.path {
+            if self.heading.is_none() && self.elapsed > delay {
+                // Dive from where the plane waited, straight down when there is no player to aim at
+                self.heading = Some(
+                    target
+                        .and_then(|target| (target - position).try_normalize())
+                        .unwrap_or(Vec2::NEG_Y),
+                );
+                self.start = position;
+            }
+        }
+        if delta <= 0.0 {
+            return Vec2::ZERO;
+        }
+        (self.position_at(self.elapsed) - position) / delta
+    }
+
+    fn bezier(&self, points: &[Vec2], t: f32) -> Vec2 {
+        // De Casteljau's algorithm, which works for any number of control points
+        let mut points: Vec<Vec2> = std::iter::once(self.start)
+            .chain(points.iter().map(|point| self.start + *point))
+            .collect();
+        while points.len() > 1 {
+            for index in 0..points.len() - 1 {
+                points[index] = points[index].lerp(points[index + 1], t);
+            }
+            points.pop();
+        }
+        points[0]
+    }
+
+    fn catmull_rom(&self, points: &[Vec2], t: f32) -> Vec2 {
+        let knots: Vec<Vec2> = std::iter::once(self.start)
+            .chain(points.iter().map(|point| self.start + *point))
+            .collect();
+        let segments = knots.len() - 1;
+        if segments == 0 {
+            return self.start;
+        }
+        let u = t * segments as f32;
+        let index = (u as usize).min(segments - 1);
+        let t = u - index as f32;
+        // The ends of the curve are given a tangent by repeating their point
+        let p0 = knots[index.saturating_sub(1)];
+        let p1 = knots[index];
+        let p2 = knots[index + 1];
+        let p3 = knots[(index + 2).min(segments)];
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+    }
+}
+
+// How far along a path of `total` seconds the plane is after `time` seconds, from 0 to 1
+fn progress(time: f32, total: f32) -> f32 {
+    match total > 0.0 {
+        true => (time / total).clamp(0.0, 1.0),
+        false => 1.0,
+    }
+}
+
+// The angle of a turn made once every `period` seconds, after `time` seconds
+fn phase(time: f32, period: f32) -> f32 {
+    match period == 0.0 {
+        true => 0.0,
+        false => TAU * time / period,
+    }
+}
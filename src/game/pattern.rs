@@ -0,0 +1,140 @@
+//! This is the bullet patterns of the guns of Thunder.
+//! Instead of a single bullet, each shot of a gun can be a spread fanned out around its direction,
+//! a ring all around the plane, a spiral that turns from one shot to the next,
+//! a burst of bullets fired one after another, or each of a list of patterns in turn.
+//! The angles of a pattern are taken from the direction of the gun,
+//! so a pattern on a gun that traces the player is aimed at the player.
+
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// What a gun fires on each shot, as written in a level file
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub enum BulletPattern {
+    // One bullet in the direction of the gun
+    #[default]
+    Single,
+    // `count` bullets fanned out evenly over `angle` radians, centered on the direction of the gun
+    Spread {
+        count: u32,
+        angle: f32,
+    },
+    // `count` bullets evenly all around the plane, the first in the direction of the gun
+    Ring {
+        count: u32,
+    },
+    // A ring of `arms` bullets that turns by `rotation` radians from one shot to the next
+    Spiral {
+        arms: u32,
+        rotation: f32,
+    },
+    // `count` bullets in the direction of the gun, one every `interval` seconds
+    Burst {
+        count: u32,
+        interval: f32,
+    },
+    // Each of `patterns` in turn, one per shot
+    Alternate(Vec<BulletPattern>),
+}
+
+impl BulletPattern {
+    /// The directions of the bullets fired right away on shot number `shot`,
+    /// as angles from the direction of the gun
+    pub fn offsets(&self, shot: u32) -> Vec<f32> {
+        match self {
+            BulletPattern::Single | BulletPattern::Burst { .. } => vec![0.0],
+            BulletPattern::Spread { count, angle } => match count {
+                0 => Vec::new(),
+                1 => vec![0.0],
+                _ => (0..*count)
+                    .map(|index| -angle / 2.0 + angle * index as f32 / (count - 1) as f32)
+                    .collect(),
+            },
+            BulletPattern::Ring { count } => ring(*count, 0.0),
+            BulletPattern::Spiral { arms, rotation } => ring(*arms, rotation * shot as f32),
+            BulletPattern::Alternate(patterns) => match alternate(patterns, shot) {
+                Some((pattern, shot)) => pattern.offsets(shot),
+                None => vec![0.0],
+            },
+        }
+    }
+
+    /// The bullets still to come after the first one on shot number `shot`,
+    /// and the seconds between them, when the shot is a burst
+    pub fn burst(&self, shot: u32) -> Option<(u32, f32)> {
+        match self {
+            BulletPattern::Burst { count, interval } if *count > 1 => Some((count - 1, *interval)),
+            BulletPattern::Alternate(patterns) => {
+                let (pattern, shot) = alternate(patterns, shot)?;
+                pattern.burst(shot)
+            }
+            _ => None,
+        }
+    }
+}
+
+// `count` angles evenly around the circle, starting at `start`
+fn ring(count: u32, start: f32) -> Vec<f32> {
+    (0..count)
+        .map(|index| start + TAU * index as f32 / count as f32)
+        .collect()
+}
+
+// The pattern that fires shot number `shot` of an alternation, and which of its own shots it is
+fn alternate(patterns: &[BulletPattern], shot: u32) -> Option<(&BulletPattern, u32)> {
+    let len = patterns.len() as u32;
+    match len {
+        0 => None,
+        _ => Some((&patterns[(shot % len) as usize], shot / len)),
+    }
+}
+
+/// A pattern a gun fires, and how far through it the gun is
+#[derive(Clone, Debug, Default)]
+pub struct Emitter {
+    pub pattern: BulletPattern,
+    // The shots fired so far, a burst counting as one
+    shots: u32,
+    // The bullets left in the burst being fired, and when the next one comes
+    burst: Option<(u32, Timer)>,
+}
+
+impl Emitter {
+    pub fn new(pattern: BulletPattern) -> Self {
+        Emitter {
+            pattern,
+            ..default()
+        }
+    }
+
+    /// The directions of the bullets to fire in a tick of `delta`, as angles from the direction of the gun.
+    /// `shoot` is whether the gun shoots in this tick.
+    pub fn tick(&mut self, shoot: bool, delta: Duration) -> Vec<f32> {
+        let mut offsets = Vec::new();
+        if let Some((left, timer)) = &mut self.burst {
+            if timer.tick(delta).just_finished() {
+                offsets.push(0.0);
+                *left -= 1;
+                if *left == 0 {
+                    self.burst = None;
+                }
+            }
+        }
+        if shoot {
+            let shot = self.shots;
+            self.shots = self.shots.wrapping_add(1);
+            offsets.extend(self.pattern.offsets(shot));
+            // A new burst cuts the one before it short
+            self.burst = self.pattern.burst(shot).map(|(left, interval)| {
+                (
+                    left,
+                    Timer::from_seconds(interval.max(0.0), TimerMode::Repeating),
+                )
+            });
+        }
+        offsets
+    }
+}
@@ -0,0 +1,220 @@
+//! This is the bookkeeping of the bullets and laser rays of Thunder.
+//! Their meshes and materials are shared instead of being added for every shot,
+//! and the bullets that leave the game are kept aside to be shot again,
+//! so that dense waves don't keep allocating new assets and entities.
+//! Bullets that fly out of the arena are taken out of the game, the number of bullets is capped,
+//! and the counts can be shown in a debug overlay with F3.
+
+use std::collections::HashMap;
+
+use bevy::{prelude::*, sprite::Mesh2dHandle};
+
+use super::arena::Arena;
+use super::collider::Collider;
+use super::graze::Graze;
+use super::laser::LaserRay;
+use super::{Bullet, Damage, Faction, OnGameScreen, Velocity, TEXT_COLOR, WALL_THICKNESS};
+
+// The most bullets that can be in the game at once, unless the app sets another `MaxBullets`
+pub const MAX_BULLETS: usize = 2000;
+
+const OVERLAY_FONT_SIZE: f32 = 20.0;
+const OVERLAY_PADDING: Val = Val::Px(5.0);
+
+/// The mesh and material handles shared by all the projectiles.
+/// The meshes have a unit size and are scaled by the transform of each projectile,
+/// so bullets of every size share a single mesh, and only one material is made per color.
+#[derive(Resource)]
+pub struct ProjectileAssets {
+    bullet_mesh: Mesh2dHandle,
+    laserray_mesh: Mesh2dHandle,
+    materials: HashMap<[u32; 4], Handle<ColorMaterial>>,
+}
+
+impl ProjectileAssets {
+    pub fn bullet_mesh(&self) -> Mesh2dHandle {
+        self.bullet_mesh.clone()
+    }
+
+    pub fn laserray_mesh(&self) -> Mesh2dHandle {
+        self.laserray_mesh.clone()
+    }
+
+    /// The material of `color`, made the first time the color is asked for
+    pub fn material(
+        &mut self,
+        materials: &mut Assets<ColorMaterial>,
+        color: Color,
+    ) -> Handle<ColorMaterial> {
+        // Colors can't be hashed, so they are looked up by the bits of their components
+        let key = color.as_rgba_f32().map(f32::to_bits);
+        self.materials
+            .entry(key)
+            .or_insert_with(|| materials.add(color))
+            .clone()
+    }
+}
+
+pub(super) fn setup_projectile_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ProjectileAssets {
+        bullet_mesh: meshes.add(Circle::default()).into(),
+        laserray_mesh: meshes.add(Rectangle::default()).into(),
+        materials: HashMap::new(),
+    });
+}
+
+/// The most bullets that can be in the game at once.
+/// When a shot goes over it, the oldest bullets are taken out of the game.
+#[derive(Resource, Clone, Copy)]
+pub struct MaxBullets(pub usize);
+
+/// The order in which the bullets were shot, to find the oldest ones
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ShotNumber(pub u64);
+
+/// The bullets that have left the game and wait, hidden, to be shot again
+#[derive(Resource, Default)]
+pub struct BulletPool {
+    entities: Vec<Entity>,
+    shots: u64,
+}
+
+impl BulletPool {
+    /// Shoot a bullet, reusing a pooled bullet entity when there is one
+    pub fn spawn(&mut self, commands: &mut Commands, bullet: impl Bundle) {
+        let shot_number = ShotNumber(self.shots);
+        self.shots += 1;
+        match self.entities.pop() {
+            Some(entity) => {
+                commands.entity(entity).insert((bullet, shot_number));
+            }
+            None => {
+                commands.spawn((bullet, shot_number));
+            }
+        }
+    }
+
+    /// Take a bullet out of the game and keep its entity for a later shot
+    pub fn recycle(&mut self, commands: &mut Commands, entity: Entity) {
+        commands
+            .entity(entity)
+            .remove::<(
+                Bullet,
+                Collider,
+                Faction,
+                Damage,
+                Velocity,
+                ShotNumber,
+                Graze,
+            )>()
+            .insert(Visibility::Hidden);
+        self.entities.push(entity);
+    }
+
+    /// The number of bullet entities waiting to be shot again
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+// The pooled bullets are despawned with the game screen, so every game starts with an empty pool
+pub(super) fn setup_bullet_pool(mut commands: Commands) {
+    commands.insert_resource(BulletPool::default());
+}
+
+// Take out the bullets that have flown past the walls, as nothing can stop them anymore,
+// then the oldest bullets while there are more than the cap
+pub(super) fn cull_bullets(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    max_bullets: Res<MaxBullets>,
+    mut bullet_pool: ResMut<BulletPool>,
+    bullet_query: Query<(Entity, &Transform, &ShotNumber), With<Bullet>>,
+) {
+    let bounds = Rect::new(
+        arena.left() - WALL_THICKNESS,
+        arena.bottom() - WALL_THICKNESS,
+        arena.right() + WALL_THICKNESS,
+        arena.top() + WALL_THICKNESS,
+    );
+    let mut bullets = Vec::new();
+    for (entity, transform, shot_number) in &bullet_query {
+        if bounds.contains(transform.translation.truncate()) {
+            bullets.push((*shot_number, entity));
+        } else {
+            bullet_pool.recycle(&mut commands, entity);
+        }
+    }
+
+    if bullets.len() > max_bullets.0 {
+        let excess = bullets.len() - max_bullets.0;
+        bullets.sort_unstable();
+        for &(_, entity) in &bullets[..excess] {
+            bullet_pool.recycle(&mut commands, entity);
+        }
+    }
+}
+
+/// Whether the projectile counts are shown, toggled with F3
+#[derive(Resource, Default)]
+pub struct ShowProjectileOverlay(pub bool);
+
+// Tag component used to tag the projectile counts
+#[derive(Component)]
+pub(super) struct ProjectileOverlay;
+
+pub(super) fn setup_projectile_overlay(mut commands: Commands, show: Res<ShowProjectileOverlay>) {
+    let mut overlay = TextBundle::from_section(
+        "",
+        TextStyle {
+            font_size: OVERLAY_FONT_SIZE,
+            color: TEXT_COLOR,
+            ..default()
+        },
+    )
+    .with_style(Style {
+        position_type: PositionType::Absolute,
+        bottom: OVERLAY_PADDING,
+        right: OVERLAY_PADDING,
+        ..default()
+    });
+    overlay.visibility = overlay_visibility(show.0);
+    commands.spawn((ProjectileOverlay, overlay, OnGameScreen));
+}
+
+fn overlay_visibility(show: bool) -> Visibility {
+    match show {
+        true => Visibility::Inherited,
+        false => Visibility::Hidden,
+    }
+}
+
+pub(super) fn update_projectile_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut show: ResMut<ShowProjectileOverlay>,
+    max_bullets: Res<MaxBullets>,
+    bullet_pool: Res<BulletPool>,
+    bullet_query: Query<(), With<Bullet>>,
+    laserray_query: Query<(), With<LaserRay>>,
+    mut overlay_query: Query<(&mut Text, &mut Visibility), With<ProjectileOverlay>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        show.0 = !show.0;
+    }
+    for (mut text, mut visibility) in &mut overlay_query {
+        *visibility = overlay_visibility(show.0);
+        if show.0 {
+            text.sections[0].value = format!(
+                "Bullets: {}/{} | Pooled: {} | Laser rays: {}",
+                bullet_query.iter().count(),
+                max_bullets.0,
+                bullet_pool.len(),
+                laserray_query.iter().count(),
+            );
+        }
+    }
+}
@@ -0,0 +1,424 @@
+//! This is the input recording and replay of Thunder.
+//! The player input is sampled once per fixed step, so that feeding the recorded inputs back
+//! with the same level and seed reproduces the run exactly.
+//! Runs are saved to `replays/`, and a replay is played from the main menu or with `--replay <file>`.
+//! While a replay is played, it can be paused, sped up, slowed down, stepped and seeked.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{app::FixedMain, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use super::{LevelSeed, OnGameScreen, Scoreboard, SCOREBOARD_FONT_SIZE, TEXT_COLOR};
+use crate::{GameState, Level, ReplaySetting};
+
+const REPLAY_DIR: &str = "replays";
+
+// Playback speeds, picked with the keys 1 to 4
+const REPLAY_SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+// How far the left and right arrow keys seek
+const REPLAY_SEEK_SECONDS: f32 = 5.0;
+const REPLAY_HUD_PADDING: Val = Val::Px(5.0);
+
+/// The player input of one fixed step
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct PlayerInput {
+    pub direction: Vec2,
+    pub laser: bool,
+    // Older replays were recorded before focusing existed
+    #[serde(default)]
+    pub focus: bool,
+}
+
+impl PlayerInput {
+    fn from_keyboard(keyboard_input: &ButtonInput<KeyCode>) -> Self {
+        let mut direction = Vec2::ZERO;
+        if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
+            direction.x -= 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
+            direction.x += 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::ArrowDown) || keyboard_input.pressed(KeyCode::KeyS) {
+            direction.y -= 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::ArrowUp) || keyboard_input.pressed(KeyCode::KeyW) {
+            direction.y += 1.0;
+        }
+        PlayerInput {
+            direction,
+            laser: keyboard_input.pressed(KeyCode::KeyL),
+            focus: keyboard_input.pressed(KeyCode::ShiftLeft)
+                || keyboard_input.pressed(KeyCode::ShiftRight),
+        }
+    }
+}
+
+// The same input held for a number of consecutive fixed steps
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct InputSpan {
+    input: PlayerInput,
+    steps: u32,
+}
+
+/// How a recorded run ended
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub enum Outcome {
+    Win,
+    Lose,
+    Completion,
+    // The player went back to the main menu
+    #[default]
+    Quit,
+}
+
+/// A recorded run: the level, the seed of the random generator and the inputs of every fixed step
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Replay {
+    pub level: u32,
+    pub seed: u64,
+    #[serde(default)]
+    pub score: u32,
+    #[serde(default)]
+    pub outcome: Outcome,
+    inputs: Vec<InputSpan>,
+}
+
+impl Replay {
+    fn push(&mut self, input: PlayerInput) {
+        match self.inputs.last_mut() {
+            Some(span) if span.input == input => span.steps += 1,
+            _ => self.inputs.push(InputSpan { input, steps: 1 }),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        ron::from_str(&content).map_err(|error| error.to_string())
+    }
+
+    fn save(&self) -> Result<String, String> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|error| error.to_string())?
+            .as_secs();
+        let path = format!(
+            "{}/{}_level{}.replay.ron",
+            REPLAY_DIR, timestamp, self.level
+        );
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())?;
+        std::fs::create_dir_all(REPLAY_DIR).map_err(|error| error.to_string())?;
+        std::fs::write(&path, content).map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+}
+
+/// What the replays menu shows of a recorded run
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct ReplaySummary {
+    pub level: u32,
+    #[serde(default)]
+    pub score: u32,
+    #[serde(default)]
+    pub outcome: Outcome,
+}
+
+impl ReplaySummary {
+    /// Read the summary of the run recorded in `path`.
+    /// The whole file is still read and parsed, but its inputs aren't kept.
+    pub fn load(path: &Path) -> Result<ReplaySummary, String> {
+        let content = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        ron::from_str(&content).map_err(|error| error.to_string())
+    }
+}
+
+/// The files of the recorded runs in the replay directory, the newest first.
+/// The files themselves aren't read, however many of them there are.
+pub fn list_replays() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(REPLAY_DIR) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.to_string_lossy().ends_with(".replay.ron"))
+        .collect();
+    // The file names start with the time of the recording
+    paths.sort();
+    paths.reverse();
+    paths
+}
+
+/// Whether finished runs are written to the replay directory
+#[derive(Resource)]
+pub struct SaveReplays(pub bool);
+
+// The inputs of the run being played
+#[derive(Resource)]
+pub(super) struct InputRecorder(pub(super) Replay);
+
+/// The replay being played. While it exists, the player input comes from it instead of the keyboard
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    replay: Replay,
+    span: usize,
+    step: u32,
+    // The number of fixed steps played since the start of the level
+    tick: u32,
+    speed: f32,
+    paused: bool,
+    // Fixed steps to run at once, for frame stepping and seeking
+    pending_steps: u32,
+    // The tick to seek to once the level has restarted
+    seek_to: Option<u32>,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            span: 0,
+            step: 0,
+            tick: 0,
+            speed: 1.0,
+            paused: false,
+            pending_steps: 0,
+            seek_to: None,
+        }
+    }
+
+    pub(super) fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+
+    fn next_input(&mut self) -> PlayerInput {
+        self.tick += 1;
+        // Once the replay is over, the plane stays still
+        let Some(span) = self.replay.inputs.get(self.span) else {
+            return PlayerInput::default();
+        };
+        let input = span.input;
+        self.step += 1;
+        if self.step >= span.steps {
+            self.span += 1;
+            self.step = 0;
+        }
+        input
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "Replay {}x{}  [Space] pause  [1-4] speed  [.] step  [Left/Right] seek",
+            self.speed,
+            if self.paused { " (paused)" } else { "" },
+        )
+    }
+}
+
+// Tag component used to tag the playback status of the replay
+#[derive(Component)]
+pub(super) struct ReplayHud;
+
+// Start the replay given with `--replay <file>` right after launching the game
+pub(super) fn start_replay_from_setting(
+    mut commands: Commands,
+    replay_setting: Res<ReplaySetting>,
+    mut level: ResMut<Level>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(path) = &replay_setting.0 else {
+        return;
+    };
+    match Replay::load(path) {
+        Ok(replay) => {
+            info!(
+                "Replaying {} (level {}, seed {})",
+                path, replay.level, replay.seed
+            );
+            *level = Level(replay.level);
+            commands.insert_resource(ReplayPlayer::new(replay));
+            game_state.set(GameState::LevelSplash);
+        }
+        Err(error) => error!("Could not load replay {}: {}", path, error),
+    }
+}
+
+// Start recording the run, and rewind the replay if there is one
+pub(super) fn start_recording(
+    mut commands: Commands,
+    level: Res<Level>,
+    seed: Res<LevelSeed>,
+    replay_player: Option<ResMut<ReplayPlayer>>,
+) {
+    commands.insert_resource(PlayerInput::default());
+    commands.insert_resource(InputRecorder(Replay {
+        level: level.0,
+        seed: seed.0,
+        ..default()
+    }));
+    if let Some(mut replay_player) = replay_player {
+        replay_player.span = 0;
+        replay_player.step = 0;
+        replay_player.tick = 0;
+        // A seek backwards restarts the level, then runs up to the wanted tick
+        replay_player.pending_steps = replay_player.seek_to.take().unwrap_or(0);
+
+        commands.spawn((
+            ReplayHud,
+            TextBundle::from_section(
+                replay_player.status(),
+                TextStyle {
+                    font_size: SCOREBOARD_FONT_SIZE / 2.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                bottom: REPLAY_HUD_PADDING,
+                left: REPLAY_HUD_PADDING,
+                ..default()
+            }),
+            OnGameScreen,
+        ));
+    }
+}
+
+// Sample the input of this fixed step, from the replay or from the keyboard, and record it
+pub(super) fn read_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut player_input: ResMut<PlayerInput>,
+    mut recorder: ResMut<InputRecorder>,
+    replay_player: Option<ResMut<ReplayPlayer>>,
+) {
+    *player_input = match replay_player {
+        Some(mut replay_player) => replay_player.next_input(),
+        None => PlayerInput::from_keyboard(&keyboard_input),
+    };
+    recorder.0.push(*player_input);
+}
+
+// Write the recorded run to the replay directory when the run is over
+pub(super) fn save_recording(
+    mut commands: Commands,
+    recorder: Option<ResMut<InputRecorder>>,
+    replay_player: Option<Res<ReplayPlayer>>,
+    game_state: Res<State<GameState>>,
+    scoreboard: Option<Res<Scoreboard>>,
+    save_replays: Res<SaveReplays>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    // There is no need to record a replay again
+    if replay_player.is_none() && save_replays.0 {
+        recorder.0.score = scoreboard.map_or(0, |scoreboard| scoreboard.score);
+        recorder.0.outcome = match game_state.get() {
+            GameState::Win => Outcome::Win,
+            GameState::Lose => Outcome::Lose,
+            GameState::Completion => Outcome::Completion,
+            _ => Outcome::Quit,
+        };
+        match recorder.0.save() {
+            Ok(path) => info!("Replay saved to {}", path),
+            Err(error) => warn!("Could not save replay: {}", error),
+        }
+    }
+    commands.remove_resource::<InputRecorder>();
+}
+
+// A replay that is over, or whose level couldn't be played, gives the control back
+// to the keyboard and the time back to normal
+pub(super) fn stop_replay(
+    mut commands: Commands,
+    replay_player: Option<Res<ReplayPlayer>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if replay_player.is_none() {
+        return;
+    }
+    commands.remove_resource::<ReplayPlayer>();
+    time.unpause();
+    time.set_relative_speed(1.0);
+}
+
+// Handle the playback keys while a replay is played
+pub(super) fn control_replay(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut replay_player: ResMut<ReplayPlayer>,
+    mut time: ResMut<Time<Virtual>>,
+    fixed_time: Res<Time<Fixed>>,
+    game_screen_query: Query<Entity, With<OnGameScreen>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        replay_player.paused = !replay_player.paused;
+        match replay_player.paused {
+            true => time.pause(),
+            false => time.unpause(),
+        }
+    }
+    let speed_keys = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+    ];
+    for (key, speed) in speed_keys.into_iter().zip(REPLAY_SPEEDS) {
+        if keyboard_input.just_pressed(key) {
+            replay_player.speed = speed;
+            // The fixed steps keep their length, there are just more or fewer of them per second
+            time.set_relative_speed(speed);
+        }
+    }
+    if replay_player.paused && keyboard_input.just_pressed(KeyCode::Period) {
+        replay_player.pending_steps += 1;
+    }
+
+    let seek_steps = (REPLAY_SEEK_SECONDS / fixed_time.timestep().as_secs_f32()) as u32;
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        replay_player.pending_steps += seek_steps;
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        // The simulation can't run backwards, so the level is played again from its start
+        replay_player.seek_to = Some(replay_player.tick.saturating_sub(seek_steps));
+        replay_player.pending_steps = 0;
+        for entity in &game_screen_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        game_state.set(GameState::Init);
+    }
+}
+
+pub(super) fn update_replay_hud(
+    replay_player: Res<ReplayPlayer>,
+    mut hud_query: Query<&mut Text, With<ReplayHud>>,
+) {
+    if replay_player.is_changed() {
+        for mut text in &mut hud_query {
+            text.sections[0].value = replay_player.status();
+        }
+    }
+}
+
+// Run the fixed steps asked for by frame stepping and seeking at once
+pub(super) fn run_replay_steps(world: &mut World) {
+    let steps = std::mem::take(&mut world.resource_mut::<ReplayPlayer>().pending_steps);
+    if steps == 0 {
+        return;
+    }
+    // Run the steps the same way the fixed timestep loop does
+    let fixed_time = world.resource::<Time<Fixed>>().as_generic();
+    *world.resource_mut::<Time>() = fixed_time;
+    for _ in 0..steps {
+        // Stop once the run is over, as the state only changes at the next frame
+        if world.resource::<NextState<GameState>>().0.is_some() {
+            break;
+        }
+        world.run_schedule(FixedMain);
+    }
+    let virtual_time = world.resource::<Time<Virtual>>().as_generic();
+    *world.resource_mut::<Time>() = virtual_time;
+}
@@ -0,0 +1,311 @@
+//! This is the schedule of the wave being played in Thunder.
+//! The enemies of a wave don't have to come all at once: each one comes after its spawn delay,
+//! reinforcements come when the leader of the wave is hurt or after some time,
+//! and a wave with a time limit gives way to the next one even if some of its enemies are left.
+//! The enemies still to come are kept as they are written in the level file and only built
+//! when they come, so that they follow the file when it is edited during the wave.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+
+use super::arena::Arena;
+use super::config::{EnemyConfig, ReinforcementConfig, ReinforcementTrigger, WaveConfig};
+use super::formation::FormationRole;
+use super::{Enemy, HP};
+
+// The part of a wave an enemy comes with: the enemies it starts with,
+// or the reinforcement at that place in the level file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Group {
+    Start,
+    Reinforcement(usize),
+}
+
+// An enemy of a wave in the level file: its group and its place in the group
+type Slot = (Group, usize);
+
+/// An enemy of the wave that is waiting to come
+pub struct PendingEnemy {
+    // When it comes, in seconds from the start of the wave
+    pub time: f32,
+    pub config: EnemyConfig,
+    // Where the formation it flies in settles, its own position is generated when it comes otherwise
+    pub position: Option<Vec2>,
+    // Its place in its formation
+    pub offset: Vec2,
+    pub formation_role: Option<FormationRole>,
+    // Whether it is the leader of the wave, whose HP the reinforcements may wait for
+    pub leads: bool,
+    slot: Slot,
+}
+
+/// The enemies and reinforcements still to come in the wave being played
+#[derive(Resource, Default)]
+pub struct WaveSchedule {
+    // The wave being played, if any
+    wave: Option<u32>,
+    // Seconds since the wave started
+    elapsed: f32,
+    pending: Vec<PendingEnemy>,
+    // The reinforcements that haven't been called in, with their place in the level file
+    reinforcements: Vec<(usize, ReinforcementConfig)>,
+    // The reinforcements that have been called in: their place, when, and the number of their formation
+    called: Vec<(usize, f32, u32)>,
+    // The enemies that have come
+    spawned: Vec<Slot>,
+    time_limit: Option<f32>,
+    // The leader of the wave and the HP it came with, once it has come
+    leader: Option<(Entity, u32)>,
+    // The number of the formation the wave starts with
+    formation: u32,
+    // The number of formations so far in the level, so that each is known by its own number
+    formations: u32,
+}
+
+impl WaveSchedule {
+    /// Start the wave numbered `wave`, written as `wave_config`
+    pub fn start(
+        &mut self,
+        wave: u32,
+        wave_config: &WaveConfig,
+        arena: &Arena,
+        rng: &mut GlobalEntropy<WyRand>,
+    ) {
+        self.clear();
+        self.wave = Some(wave);
+        self.formation = self.new_formation();
+        self.pending = plan(wave_config, Group::Start, self.formation, arena, rng);
+        if let WaveConfig::Scripted(scripted) = wave_config {
+            self.reinforcements = scripted
+                .reinforcements
+                .iter()
+                .cloned()
+                .enumerate()
+                .collect();
+            self.time_limit = scripted.time_limit;
+        }
+    }
+
+    /// The wave being played, if any
+    pub fn wave(&self) -> Option<u32> {
+        self.wave
+    }
+
+    /// Plan the rest of the wave again from the level file, once it has been edited.
+    /// The enemies that have come and the reinforcements that have been called in stay as they are.
+    pub fn replan(
+        &mut self,
+        wave_config: Option<&WaveConfig>,
+        arena: &Arena,
+        rng: &mut GlobalEntropy<WyRand>,
+    ) {
+        if self.wave.is_none() {
+            return;
+        }
+        let old_pending = std::mem::take(&mut self.pending);
+        self.reinforcements.clear();
+        self.time_limit = None;
+        // The wave may have been taken out of the file
+        let Some(wave_config) = wave_config else {
+            return;
+        };
+        let mut pending = plan(wave_config, Group::Start, self.formation, arena, rng);
+        if let WaveConfig::Scripted(scripted) = wave_config {
+            for (index, time, formation) in &self.called {
+                let Some(reinforcement) = scripted.reinforcements.get(*index) else {
+                    continue;
+                };
+                let group = Group::Reinforcement(*index);
+                pending.extend(
+                    plan(&reinforcement.enemies, group, *formation, arena, rng)
+                        .into_iter()
+                        .map(|enemy| PendingEnemy {
+                            time: time + enemy.time,
+                            leads: false,
+                            ..enemy
+                        }),
+                );
+            }
+            self.reinforcements = scripted
+                .reinforcements
+                .iter()
+                .cloned()
+                .enumerate()
+                .filter(|(index, _)| !self.called.iter().any(|(called, ..)| called == index))
+                .collect();
+            self.time_limit = scripted.time_limit;
+        }
+        pending.retain(|enemy| !self.spawned.contains(&enemy.slot));
+        // A formation that is partly in the arena keeps its place
+        for enemy in &mut pending {
+            if let Some(old) = old_pending
+                .iter()
+                .find(|old| old.slot.0 == enemy.slot.0 && old.position.is_some())
+            {
+                enemy.position = enemy.position.and(old.position);
+            }
+        }
+        self.pending = pending;
+    }
+
+    /// Forget the rest of the wave
+    pub fn clear(&mut self) {
+        self.wave = None;
+        self.elapsed = 0.0;
+        self.pending.clear();
+        self.reinforcements.clear();
+        self.called.clear();
+        self.spawned.clear();
+        self.time_limit = None;
+        self.leader = None;
+    }
+
+    // The number of a formation that is about to be generated
+    fn new_formation(&mut self) -> u32 {
+        self.formations += 1;
+        self.formations
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.elapsed += delta;
+    }
+
+    /// Take out the enemies whose time to come has come
+    pub fn take_due(&mut self) -> Vec<PendingEnemy> {
+        let (due, pending): (Vec<PendingEnemy>, _) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|enemy| enemy.time <= self.elapsed);
+        self.pending = pending;
+        self.spawned.extend(due.iter().map(|enemy| enemy.slot));
+        due
+    }
+
+    pub fn set_leader(&mut self, leader: Entity, hp: u32) {
+        self.leader = Some((leader, hp));
+    }
+
+    /// Plan the reinforcements that have been called in, which come from now on.
+    /// A leader that is gone, or a wave without one, counts as having no HP left.
+    pub fn call_reinforcements(
+        &mut self,
+        hp_query: &Query<&HP, With<Enemy>>,
+        arena: &Arena,
+        rng: &mut GlobalEntropy<WyRand>,
+    ) {
+        let leader_health = match self.leader {
+            Some((leader, max_hp)) => match hp_query.get(leader) {
+                Ok(hp) => Some(hp.0 as f32 / max_hp.max(1) as f32),
+                Err(_) => Some(0.0),
+            },
+            // The leader may still be waiting to come
+            None if self.pending.iter().any(|enemy| enemy.leads) => None,
+            None => Some(0.0),
+        };
+        let elapsed = self.elapsed;
+        let (triggered, waiting): (Vec<_>, _) = std::mem::take(&mut self.reinforcements)
+            .into_iter()
+            .partition(|(_, reinforcement)| match reinforcement.trigger {
+                ReinforcementTrigger::After(time) => elapsed >= time,
+                ReinforcementTrigger::LeaderHpBelow(fraction) => {
+                    leader_health.is_some_and(|health| health < fraction)
+                }
+            });
+        self.reinforcements = waiting;
+        for (index, reinforcement) in triggered {
+            info!("Reinforcements are coming.");
+            let formation = self.new_formation();
+            let group = Group::Reinforcement(index);
+            let enemies = plan(&reinforcement.enemies, group, formation, arena, rng);
+            self.pending
+                .extend(enemies.into_iter().map(|enemy| PendingEnemy {
+                    time: elapsed + enemy.time,
+                    leads: false,
+                    ..enemy
+                }));
+            self.called.push((index, elapsed, formation));
+        }
+    }
+
+    /// Whether every enemy of the wave has come.
+    /// Reinforcements that haven't been called in hold up the next wave,
+    /// unless they are called off once the wave is cleared.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+            && self
+                .reinforcements
+                .iter()
+                .all(|(_, reinforcement)| reinforcement.cancel_on_clear)
+    }
+
+    /// Whether the time limit of the wave is over
+    pub fn timed_out(&self) -> bool {
+        self.time_limit
+            .is_some_and(|time_limit| self.elapsed >= time_limit)
+    }
+}
+
+// The enemies of `wave_config` in `group`, whose formation, if any, is known by `formation`.
+// The first of them leads the wave.
+fn plan(
+    wave_config: &WaveConfig,
+    group: Group,
+    formation: u32,
+    arena: &Arena,
+    rng: &mut GlobalEntropy<WyRand>,
+) -> Vec<PendingEnemy> {
+    let pending = |config: &EnemyConfig, position, offset, formation_role| PendingEnemy {
+        time: config.spawn_delay,
+        config: config.clone(),
+        position,
+        offset,
+        formation_role,
+        leads: false,
+        slot: (group, 0),
+    };
+    let mut enemies: Vec<PendingEnemy> = match wave_config {
+        WaveConfig::Duplicate(enemy_config, enemy_num) => (0..*enemy_num)
+            .map(|_| pending(enemy_config, None, Vec2::ZERO, None))
+            .collect(),
+        WaveConfig::Detailed(enemy_configs) => enemy_configs
+            .iter()
+            .map(|enemy_config| pending(enemy_config, None, Vec2::ZERO, None))
+            .collect(),
+        // The whole formation settles around where its leader does
+        WaveConfig::Formation(formation_config) => {
+            let position = formation_config.leader.position.gen(arena, rng);
+            let member_config = formation_config.member_config();
+            formation_config
+                .shape
+                .offsets(formation_config.count, formation_config.spacing)
+                .into_iter()
+                .enumerate()
+                .map(|(index, offset)| match index {
+                    0 => pending(
+                        &formation_config.leader,
+                        Some(position),
+                        offset,
+                        Some(FormationRole::Leader(formation)),
+                    ),
+                    _ => pending(
+                        &member_config,
+                        Some(position),
+                        offset,
+                        Some(FormationRole::Member {
+                            formation,
+                            offset,
+                            on_leader_death: formation_config.on_leader_death,
+                        }),
+                    ),
+                })
+                .collect()
+        }
+        WaveConfig::Scripted(scripted) => plan(&scripted.enemies, group, formation, arena, rng),
+    };
+    for (index, enemy) in enemies.iter_mut().enumerate() {
+        enemy.slot = (group, index);
+    }
+    if let Some(leader) = enemies.first_mut() {
+        leader.leads = true;
+    }
+    enemies
+}
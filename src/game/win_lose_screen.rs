@@ -3,6 +3,7 @@
 use bevy::{app::AppExit, prelude::*};
 
 use super::{despawn_screen, GameState, Level};
+use crate::game::Scoreboard;
 
 // This plugin manages the win/lose screen, with 3 different screens:
 // - a win screen with "Main Menu", "Next Level", "Quit" buttons
@@ -92,7 +93,32 @@ fn button_system(
     }
 }
 
-fn win_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+// Display the stats of the level that just ended, under the title
+fn spawn_stats(parent: &mut ChildBuilder, scoreboard: &Scoreboard) {
+    parent.spawn(
+        TextBundle::from_section(
+            format!(
+                "Score: {}    Graze: {}    Escaped: {}",
+                scoreboard.score, scoreboard.graze, scoreboard.escaped
+            ),
+            TextStyle {
+                font_size: 30.0,
+                color: TEXT_COLOR,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::bottom(Val::Px(30.0)),
+            ..default()
+        }),
+    );
+}
+
+fn win_screen_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+) {
     // Common style for all buttons on the screen
     let button_style = Style {
         width: Val::Px(300.0),
@@ -158,6 +184,7 @@ fn win_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ..default()
                         }),
                     );
+                    spawn_stats(parent, &scoreboard);
 
                     // Display three buttons for each action available from the win screen:
                     // - Main Menu
@@ -246,7 +273,11 @@ fn win_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
-fn lose_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn lose_screen_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+) {
     // Common style for all buttons on the screen
     let button_style = Style {
         width: Val::Px(300.0),
@@ -312,6 +343,7 @@ fn lose_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                             ..default()
                         }),
                     );
+                    spawn_stats(parent, &scoreboard);
 
                     // Display three buttons for each action available from the lose screen:
                     // - Main Menu
@@ -380,7 +412,11 @@ fn lose_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
 }
 
-fn completion_screen_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn completion_screen_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    scoreboard: Res<Scoreboard>,
+) {
     // Common style for all buttons on the screen
     let button_style = Style {
         width: Val::Px(300.0),
@@ -446,6 +482,7 @@ fn completion_screen_setup(mut commands: Commands, asset_server: Res<AssetServer
                             ..default()
                         }),
                     );
+                    spawn_stats(parent, &scoreboard);
 
                     // Display three buttons for each action available from the complete screen:
                     // - Main Menu
@@ -1,9 +1,10 @@
 //! This is the page that appears after the level is selected and before the corresponding game scene will
 //! start, which will display the selected level to the user again.
-//! It will display the level for 1 second before transitioning to the main menu.
+//! It will display the level for at least 1 second, until the level file is loaded, before transitioning to the game.
 use bevy::prelude::*;
 
 use super::{despawn_screen, GameState, Level, TEXT_COLOR};
+use crate::game::level_loader::CurrentLevel;
 
 // This plugin will display a splash screen with the level information for 1 second before switching to the menu
 pub fn level_splash_plugin(app: &mut App) {
@@ -64,13 +65,15 @@ fn level_splash_setup(mut commands: Commands, level: Res<Level>) {
     commands.insert_resource(SplashTimer(Timer::from_seconds(1.0, TimerMode::Once)));
 }
 
-// Tick the timer, and change state when finished
+// Tick the timer, and change state when finished and the level is ready
 fn countdown(
     mut game_state: ResMut<NextState<GameState>>,
     time: Res<Time>,
     mut timer: ResMut<SplashTimer>,
+    asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
 ) {
-    if timer.tick(time.delta()).finished() {
+    if timer.tick(time.delta()).finished() && current_level.is_loaded(&asset_server) {
         game_state.set(GameState::Init);
     }
 }
@@ -1,8 +1,12 @@
 //! This is the menu page together with the setting page. The volume and
 //! display quality settings are yet to implement.
 
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use bevy::{app::AppExit, prelude::*};
 
+use crate::game::replay::{list_replays, Replay, ReplayPlayer, ReplaySummary};
 use crate::Level;
 
 use super::{despawn_screen, DisplayQuality, GameState, Volume, TEXT_COLOR};
@@ -14,8 +18,9 @@ pub const HELP_SCREEN: &str = "1. Use the arrow keys or w, a, s, and d to move t
         4. Avoid being shot by the enemy and get the highest score possible.\n\
         5. There are 5 levels to play. Good Luck!";
 
-// This plugin manages the menu, with 5 different screens:
-// - a main menu with "New Game", "Settings", "Quit"
+// This plugin manages the menu, with 6 different screens:
+// - a main menu with "New Game", "Replays", "Settings", "Quit"
+// - a replays menu listing the recorded runs
 // - a settings menu with two submenus and a back button
 // - two settings screen with a setting that can be set and a back button
 pub fn menu_plugin(app: &mut App) {
@@ -35,6 +40,21 @@ pub fn menu_plugin(app: &mut App) {
             OnExit(MenuState::Levels),
             despawn_screen::<OnLevelsMenuScreen>,
         )
+        // Systems to handle the replays menu screen
+        .init_resource::<ReplayList>()
+        .add_systems(OnEnter(MenuState::Replays), replays_menu_setup)
+        .add_systems(
+            Update,
+            (
+                (replay_button, replay_page_button).run_if(in_state(MenuState::Replays)),
+                show_replay_page
+                    .run_if(in_state(MenuState::Replays).and_then(resource_changed::<ReplayList>)),
+            ),
+        )
+        .add_systems(
+            OnExit(MenuState::Replays),
+            despawn_screen::<OnReplaysMenuScreen>,
+        )
         // Systems to handle the settings menu screen
         .add_systems(OnEnter(MenuState::Settings), settings_menu_setup)
         .add_systems(
@@ -78,6 +98,7 @@ pub fn menu_plugin(app: &mut App) {
 pub enum MenuState {
     Main,
     Levels,
+    Replays,
     Settings,
     SettingsDisplay,
     SettingsSound,
@@ -93,6 +114,10 @@ struct OnMainMenuScreen;
 #[derive(Component)]
 struct OnLevelsMenuScreen;
 
+// Tag component used to tag entities added on the replays menu screen
+#[derive(Component)]
+struct OnReplaysMenuScreen;
+
 // Tag component used to tag entities added on the settings menu screen
 #[derive(Component)]
 struct OnSettingsMenuScreen;
@@ -121,6 +146,7 @@ struct SelectedOption;
 #[derive(Component)]
 enum MenuButtonAction {
     SelectLevel,
+    Replays,
     Settings,
     SettingsDisplay,
     SettingsSound,
@@ -182,6 +208,163 @@ fn level_button(
     }
 }
 
+// The number of recorded runs on a page of the replays menu
+const REPLAYS_PER_PAGE: usize = 5;
+
+// The recorded runs listed in the replays menu, and the page of them being shown.
+// The summary of a run is only read once its page is shown, and kept for the next time.
+#[derive(Resource, Default)]
+struct ReplayList {
+    paths: Vec<PathBuf>,
+    page: usize,
+    summaries: HashMap<PathBuf, Option<ReplaySummary>>,
+}
+
+impl ReplayList {
+    fn page_count(&self) -> usize {
+        self.paths.len().div_ceil(REPLAYS_PER_PAGE).max(1)
+    }
+}
+
+// Tag component used to tag the node holding the runs of the page being shown
+#[derive(Component)]
+struct ReplayRows;
+
+// Tag component used to tag the text telling which page is shown
+#[derive(Component)]
+struct ReplayPageText;
+
+// The buttons turning the pages of the replays menu
+#[derive(Component)]
+enum ReplayPageButton {
+    Previous,
+    Next,
+}
+
+// The replay file behind a button of the replays menu
+#[derive(Component)]
+struct ReplayFile(String);
+
+// This system turns the page of the replays menu
+#[allow(clippy::type_complexity)]
+fn replay_page_button(
+    interaction_query: Query<
+        (&Interaction, &ReplayPageButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut replay_list: ResMut<ReplayList>,
+) {
+    for (interaction, page_button) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            let page = match page_button {
+                ReplayPageButton::Previous => replay_list.page.saturating_sub(1),
+                ReplayPageButton::Next => (replay_list.page + 1).min(replay_list.page_count() - 1),
+            };
+            if page != replay_list.page {
+                replay_list.page = page;
+            }
+        }
+    }
+}
+
+// This system shows a button for each recorded run of the page picked in the replays menu
+fn show_replay_page(
+    mut commands: Commands,
+    mut replay_list: ResMut<ReplayList>,
+    rows_query: Query<Entity, With<ReplayRows>>,
+    mut page_text_query: Query<&mut Text, With<ReplayPageText>>,
+) {
+    let Ok(rows) = rows_query.get_single() else {
+        return;
+    };
+    // Reading the summaries isn't a change of the page
+    let replay_list = replay_list.bypass_change_detection();
+    let page_count = replay_list.page_count();
+    replay_list.page = replay_list.page.min(page_count - 1);
+    for mut text in &mut page_text_query {
+        text.sections[0].value = format!("{} / {}", replay_list.page + 1, page_count);
+    }
+    let paths: Vec<PathBuf> = replay_list
+        .paths
+        .iter()
+        .skip(replay_list.page * REPLAYS_PER_PAGE)
+        .take(REPLAYS_PER_PAGE)
+        .cloned()
+        .collect();
+    let replay_text_style = TextStyle {
+        font_size: 25.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    commands.entity(rows).despawn_descendants();
+    commands.entity(rows).with_children(|parent| {
+        if paths.is_empty() {
+            parent.spawn(TextBundle::from_section(
+                "No recorded runs yet",
+                replay_text_style.clone(),
+            ));
+        }
+        for path in paths {
+            let summary = *replay_list
+                .summaries
+                .entry(path.clone())
+                .or_insert_with(|| ReplaySummary::load(&path).ok());
+            // A run that can't be read keeps its place, so that the pages don't shift
+            let label = match summary {
+                Some(summary) => format!(
+                    "Level {} - Score {} - {:?}",
+                    summary.level, summary.score, summary.outcome
+                ),
+                None => format!("Unreadable - {}", path.to_string_lossy()),
+            };
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(500.0),
+                            height: Val::Px(45.0),
+                            margin: UiRect::all(Val::Px(5.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    ReplayFile(path.to_string_lossy().into_owned()),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(label, replay_text_style.clone()));
+                });
+        }
+    });
+}
+
+// This system plays the replay picked by the player
+#[allow(clippy::type_complexity)]
+fn replay_button(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &ReplayFile), (Changed<Interaction>, With<Button>)>,
+    mut level_setting: ResMut<Level>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, replay_file) in &interaction_query {
+        if *interaction == Interaction::Pressed {
+            match Replay::load(&replay_file.0) {
+                Ok(replay) => {
+                    *level_setting = Level(replay.level);
+                    commands.insert_resource(ReplayPlayer::new(replay));
+                    game_state.set(GameState::LevelSplash);
+                    menu_state.set(MenuState::Disabled);
+                }
+                Err(error) => error!("Could not load replay {}: {}", replay_file.0, error),
+            }
+        }
+    }
+}
+
 fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::Main);
 }
@@ -252,8 +435,9 @@ pub fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                         }),
                     );
 
-                    // Display three buttons for each action available from the main menu:
+                    // Display a button for each action available from the main menu:
                     // - new game
+                    // - replays
                     // - settings
                     // - help
                     // - quit
@@ -278,6 +462,27 @@ pub fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                                 button_text_style.clone(),
                             ));
                         });
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::Replays,
+                        ))
+                        .with_children(|parent| {
+                            let icon = asset_server.load("textures/Game Icons/retry.png");
+                            parent.spawn(ImageBundle {
+                                style: button_icon_style.clone(),
+                                image: UiImage::new(icon),
+                                ..default()
+                            });
+                            parent.spawn(TextBundle::from_section(
+                                "Replays",
+                                button_text_style.clone(),
+                            ));
+                        });
                     parent
                         .spawn((
                             ButtonBundle {
@@ -451,6 +656,137 @@ fn level_select_menu_setup(mut commands: Commands) {
         });
 }
 
+fn replays_menu_setup(mut commands: Commands, mut replay_list: ResMut<ReplayList>) {
+    let button_style = Style {
+        width: Val::Px(200.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 40.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let page_button_style = Style {
+        width: Val::Px(150.0),
+        ..button_style.clone()
+    };
+    // The newest runs come first, on the first page
+    replay_list.paths = list_replays();
+    replay_list.page = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnReplaysMenuScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: Color::CRIMSON.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section("Replays", button_text_style.clone()).with_style(
+                            Style {
+                                margin: UiRect::all(Val::Px(20.0)),
+                                ..default()
+                            },
+                        ),
+                    );
+                    // The recorded runs of the page being shown are put here by `show_replay_page`
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        ReplayRows,
+                    ));
+                    // Display the buttons to turn the pages
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: page_button_style.clone(),
+                                        background_color: NORMAL_BUTTON.into(),
+                                        ..default()
+                                    },
+                                    ReplayPageButton::Previous,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        "<",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                            parent.spawn((
+                                TextBundle::from_section("", button_text_style.clone()),
+                                ReplayPageText,
+                            ));
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: page_button_style,
+                                        background_color: NORMAL_BUTTON.into(),
+                                        ..default()
+                                    },
+                                    ReplayPageButton::Next,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section(
+                                        ">",
+                                        button_text_style.clone(),
+                                    ));
+                                });
+                        });
+                    // Display the back button to return to the main menu
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: NORMAL_BUTTON.into(),
+                                ..default()
+                            },
+                            MenuButtonAction::BackToMainMenu,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Back", button_text_style));
+                        });
+                });
+        });
+}
+
 fn settings_menu_setup(mut commands: Commands) {
     let button_style = Style {
         width: Val::Px(200.0),
@@ -824,6 +1160,7 @@ fn menu_action(
                 MenuButtonAction::SelectLevel => {
                     menu_state.set(MenuState::Levels);
                 }
+                MenuButtonAction::Replays => menu_state.set(MenuState::Replays),
                 MenuButtonAction::Settings => menu_state.set(MenuState::Settings),
                 MenuButtonAction::SettingsDisplay => {
                     menu_state.set(MenuState::SettingsDisplay);
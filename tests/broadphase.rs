@@ -0,0 +1,61 @@
+//! Tests of the broadphase that finds the targets near a bullet.
+
+use bevy::math::bounding::Aabb2d;
+use bevy::prelude::{Entity, Vec2};
+use thunder::game::broadphase::Broadphase;
+
+fn square(x: f32, y: f32, half_size: f32) -> Aabb2d {
+    Aabb2d::new(Vec2::new(x, y), Vec2::splat(half_size))
+}
+
+fn entities(shapes: impl Iterator<Item = (Entity, Aabb2d)>) -> Vec<Entity> {
+    shapes.map(|(entity, _)| entity).collect()
+}
+
+#[test]
+fn only_nearby_targets_are_found() {
+    let near = Entity::from_raw(0);
+    let far = Entity::from_raw(1);
+    let mut broadphase = Broadphase::new(64.0);
+    broadphase.rebuild([
+        (near, square(10.0, 10.0, 15.0)),
+        (far, square(400.0, -250.0, 15.0)),
+    ]);
+    assert_eq!(
+        entities(broadphase.query(square(0.0, 0.0, 10.0))),
+        vec![near]
+    );
+    assert_eq!(
+        entities(broadphase.query(square(400.0, -250.0, 10.0))),
+        vec![far]
+    );
+    assert!(entities(broadphase.query(square(-300.0, 200.0, 10.0))).is_empty());
+}
+
+#[test]
+fn large_targets_are_found_once() {
+    let wall = Entity::from_raw(0);
+    let plane = Entity::from_raw(1);
+    let mut broadphase = Broadphase::new(64.0);
+    // The wall spans many cells, and so does the area that is looked up
+    broadphase.rebuild([
+        (
+            wall,
+            Aabb2d::new(Vec2::new(0.0, 300.0), Vec2::new(455.0, 5.0)),
+        ),
+        (plane, square(0.0, 280.0, 15.0)),
+    ]);
+    assert_eq!(
+        entities(broadphase.query(Aabb2d::new(Vec2::new(0.0, 290.0), Vec2::new(200.0, 20.0)))),
+        vec![wall, plane]
+    );
+}
+
+#[test]
+fn rebuilding_forgets_the_old_targets() {
+    let mut broadphase = Broadphase::new(64.0);
+    broadphase.rebuild([(Entity::from_raw(0), square(0.0, 0.0, 15.0))]);
+    broadphase.rebuild([(Entity::from_raw(1), square(200.0, 0.0, 15.0))]);
+    assert_eq!(broadphase.len(), 1);
+    assert!(entities(broadphase.query(square(0.0, 0.0, 10.0))).is_empty());
+}
@@ -0,0 +1,76 @@
+//! Tests of how the enemy planes fly in and out of the arena.
+
+use std::time::Duration;
+
+use bevy::prelude::Vec2;
+use thunder::game::arena::Arena;
+use thunder::game::choreography::{ArenaSide, Choreography, EntryConfig, ExitConfig};
+use thunder::game::config::EnemyConfig;
+
+const TICK: Duration = Duration::from_micros(15625);
+
+// Move a plane at `position` for `ticks` ticks, as long as the choreography steers it
+fn fly(choreography: &mut Choreography, mut position: Vec2, ticks: u32) -> Vec2 {
+    for _ in 0..ticks {
+        if let Some(velocity) = choreography.steer(position, TICK) {
+            position += velocity * TICK.as_secs_f32();
+        }
+    }
+    position
+}
+
+#[test]
+fn enemies_fly_in_and_settle_at_their_position() {
+    let arena = Arena::default();
+    let position = Vec2::new(100.0, 150.0);
+    let entry = EntryConfig {
+        side: ArenaSide::Left,
+        time: 1.0,
+    };
+    let (mut choreography, start) = Choreography::new(&arena, position, Some(entry), None);
+    assert!(start.x < arena.left());
+    assert_eq!(start.y, position.y);
+    assert!(choreography.is_entering());
+    let end = fly(&mut choreography, start, 64);
+    assert!(end.distance(position) < 1e-2);
+    assert!(!choreography.is_entering());
+    // Then the plane is left to its own movement
+    assert_eq!(choreography.steer(end, TICK), None);
+}
+
+#[test]
+fn enemies_leave_after_their_time() {
+    let arena = Arena::default();
+    let position = Vec2::new(0.0, 150.0);
+    let exit = ExitConfig {
+        after: 1.0,
+        side: ArenaSide::Top,
+        speed: 200.0,
+    };
+    let (mut choreography, start) = Choreography::new(&arena, position, None, Some(exit));
+    assert_eq!(start, position);
+    assert_eq!(fly(&mut choreography, start, 63), position);
+    let end = fly(&mut choreography, position, 65);
+    assert!(end.y > position.y + 190.0);
+    assert_eq!(end.x, position.x);
+}
+
+#[test]
+fn entries_and_exits_are_read_from_the_level_file() {
+    let enemy: EnemyConfig =
+        ron::from_str("(entry: Some((side: Right)), exit: Some((after: 5.0)))").unwrap();
+    assert_eq!(
+        enemy.entry,
+        Some(EntryConfig {
+            side: ArenaSide::Right,
+            ..EntryConfig::default()
+        })
+    );
+    assert_eq!(
+        enemy.exit,
+        Some(ExitConfig {
+            after: 5.0,
+            ..ExitConfig::default()
+        })
+    );
+}
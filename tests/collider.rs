@@ -0,0 +1,41 @@
+//! Tests of the shapes that bullets, planes and walls are hit with.
+
+use bevy::prelude::Vec2;
+use thunder::game::collider::Collider;
+
+#[test]
+fn circles_touch_boxes_only_when_close_enough() {
+    let plane = Collider::rectangle(Vec2::new(30.0, 30.0));
+    let bullet = Collider::circle(10.0);
+    assert!(bullet.intersects(Vec2::new(24.0, 0.0), &plane, Vec2::ZERO));
+    assert!(!bullet.intersects(Vec2::new(26.0, 0.0), &plane, Vec2::ZERO));
+    // Next to a corner, the bullet is round and misses
+    assert!(!bullet.intersects(Vec2::new(23.0, 23.0), &plane, Vec2::ZERO));
+}
+
+#[test]
+fn capsules_are_upright() {
+    let capsule = Collider::capsule(10.0, 40.0);
+    let bullet = Collider::circle(5.0);
+    assert!(bullet.intersects(Vec2::new(0.0, 50.0), &capsule, Vec2::ZERO));
+    assert!(!bullet.intersects(Vec2::new(20.0, 0.0), &capsule, Vec2::ZERO));
+    assert!(bullet.intersects(Vec2::new(14.0, -30.0), &capsule, Vec2::ZERO));
+}
+
+#[test]
+fn compound_colliders_are_hit_on_any_part() {
+    // A wide boss with a box body and two round wings
+    let boss = Collider::Compound(vec![
+        (Vec2::ZERO, Collider::rectangle(Vec2::new(40.0, 80.0))),
+        (Vec2::new(-60.0, 0.0), Collider::circle(20.0)),
+        (Vec2::new(60.0, 0.0), Collider::circle(20.0)),
+    ]);
+    let bullet = Collider::circle(5.0);
+    assert!(bullet.intersects(Vec2::new(70.0, 0.0), &boss, Vec2::ZERO));
+    assert!(boss.intersects(Vec2::ZERO, &bullet, Vec2::new(-70.0, 0.0)));
+    // Between the body and a wing
+    assert!(!bullet.intersects(Vec2::new(32.0, 30.0), &boss, Vec2::ZERO));
+    let aabb = boss.aabb(Vec2::new(100.0, 0.0));
+    assert_eq!(aabb.min, Vec2::new(20.0, -40.0));
+    assert_eq!(aabb.max, Vec2::new(180.0, 40.0));
+}
@@ -0,0 +1,58 @@
+//! Tests of how the enemies described in level files are read.
+
+use bevy::prelude::Vec2;
+use thunder::game::collider::Collider;
+use thunder::game::config::{EnemyConfig, LevelConfig};
+use thunder::game::lives::DEFAULT_LIVES;
+use thunder::game::HARM_BULLET;
+
+#[test]
+fn bullet_damage_is_read_from_the_level_file() {
+    let enemy: EnemyConfig = ron::from_str("(bullet_damage: 40, bullet_speed: 150.0)").unwrap();
+    assert_eq!(enemy.bullet_damage, 40);
+}
+
+#[test]
+fn bullet_damage_defaults_to_the_usual_harm() {
+    let enemy: EnemyConfig = ron::from_str("(hp: 100)").unwrap();
+    assert_eq!(enemy.bullet_damage, HARM_BULLET);
+}
+
+#[test]
+fn the_hitbox_of_an_enemy_is_read_from_the_level_file() {
+    let enemy: EnemyConfig =
+        ron::from_str("(collider: Some(Capsule(radius: 20.0, half_length: 30.0)))").unwrap();
+    assert_eq!(enemy.collider, Some(Collider::capsule(20.0, 30.0)));
+    let enemy: EnemyConfig = ron::from_str("(scale: (80.0, 60.0))").unwrap();
+    assert_eq!(enemy.scale, Vec2::new(80.0, 60.0));
+    assert_eq!(enemy.collider, None);
+}
+
+#[test]
+fn lives_default_when_the_level_file_leaves_them_out() {
+    let level: LevelConfig = ron::from_str("(waves: [])").unwrap();
+    assert_eq!(level.lives, DEFAULT_LIVES);
+    let level: LevelConfig = ron::from_str("(lives: 5, waves: [])").unwrap();
+    assert_eq!(level.lives, 5);
+}
+
+#[test]
+fn contact_damage_is_read_from_the_level_file() {
+    let enemy: EnemyConfig = ron::from_str("(contact_damage: 20)").unwrap();
+    assert_eq!(enemy.contact_damage, 20);
+    // Enemies only ram the player when they are meant to
+    let enemy: EnemyConfig = ron::from_str("(hp: 100)").unwrap();
+    assert_eq!(enemy.contact_damage, 0);
+}
+
+#[test]
+fn every_level_file_can_be_read() {
+    for level in 1..=5 {
+        let path = format!("assets/levels/level{level}.level.ron");
+        let text = std::fs::read_to_string(&path).unwrap();
+        match ron::from_str::<LevelConfig>(&text) {
+            Ok(level_config) => assert!(level_config.arena.is_playable(), "{path}"),
+            Err(error) => panic!("{path}: {error}"),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Tests of which bullets hit which targets.
+
+use bevy::prelude::{KeyCode, Vec2};
+use thunder::game::config::WaveConfig;
+use thunder::game::headless::HeadlessGame;
+use thunder::game::Faction;
+
+#[test]
+fn bullets_only_hit_the_other_faction() {
+    assert!(Faction::Player.can_hit(Some(&Faction::Enemy)));
+    assert!(Faction::Enemy.can_hit(Some(&Faction::Player)));
+    assert!(!Faction::Player.can_hit(Some(&Faction::Player)));
+    assert!(!Faction::Enemy.can_hit(Some(&Faction::Enemy)));
+}
+
+#[test]
+fn walls_stop_every_bullet() {
+    assert!(Faction::Player.can_hit(None));
+    assert!(Faction::Enemy.can_hit(None));
+}
+
+fn waves(level_file: &str) -> Vec<WaveConfig> {
+    ron::from_str(level_file).unwrap()
+}
+
+#[test]
+fn enemies_do_not_shoot_each_other() {
+    let mut game = HeadlessGame::new(1, 1);
+    // The enemy at the top shoots straight down through the one below it
+    game.set_waves(waves(
+        "[Detailed([
+            (
+                position: Determinate((0.0, 200.0)),
+                bullet_direction: Determinate(4.712389),
+                shooting_interval: 0.3,
+            ),
+            (position: Determinate((0.0, 100.0)), hp: 10, shooting_interval: 1000.0),
+        ])]",
+    ));
+    // The player flies out of their line of fire, where its own bullets miss them
+    game.press(KeyCode::KeyD);
+    game.step(48);
+    game.release(KeyCode::KeyD);
+    game.step(64 * 3);
+    assert!(game.bullet_count() > 0);
+    let enemy_below = game
+        .enemies()
+        .into_iter()
+        .find(|(position, _)| *position == Vec2::new(0.0, 100.0));
+    assert_eq!(enemy_below.map(|(_, hp)| hp), Some(10));
+    assert_eq!(game.score(), 0);
+}
+
+#[test]
+fn the_player_does_not_shoot_itself() {
+    let mut game = HeadlessGame::new(1, 1);
+    // An enemy in a corner that never shoots
+    game.set_waves(waves(
+        "[Duplicate((position: Determinate((-400.0, 250.0)), shooting_interval: 1000.0), 1)]",
+    ));
+    let start = game.player_position().unwrap();
+    let mut bullets = game.bullet_count();
+    for _ in 0..64 {
+        game.step(1);
+        if game.bullet_count() > bullets {
+            break;
+        }
+        bullets = game.bullet_count();
+    }
+    assert!(game.bullet_count() > bullets);
+    // Put the player right on the bullet it has just fired, 50 above it
+    game.move_player_to(start + Vec2::new(0.0, 52.0));
+    game.step(2);
+    assert_eq!(game.player_hp(), Some(100));
+    assert!(game.bullet_count() > bullets);
+}
@@ -0,0 +1,143 @@
+//! Tests of the layouts of the enemy formations.
+
+use bevy::prelude::Vec2;
+use thunder::game::config::{LevelConfig, WaveConfig};
+use thunder::game::formation::{FormationShape, LeaderDeath};
+use thunder::game::headless::HeadlessGame;
+
+#[test]
+fn the_leader_is_at_the_origin_of_every_shape() {
+    for shape in [
+        FormationShape::Line,
+        FormationShape::V,
+        FormationShape::Grid { columns: 3 },
+        FormationShape::Ring,
+    ] {
+        let offsets = shape.offsets(7, 50.0);
+        assert_eq!(offsets.len(), 7);
+        assert_eq!(offsets[0], Vec2::ZERO);
+        // No two planes share a place
+        for (index, offset) in offsets.iter().enumerate() {
+            assert!(offsets[index + 1..]
+                .iter()
+                .all(|other| other.distance(*offset) > 1.0));
+        }
+    }
+}
+
+#[test]
+fn shapes_are_laid_out_around_the_leader() {
+    assert_eq!(
+        FormationShape::Line.offsets(3, 50.0),
+        [Vec2::ZERO, Vec2::new(50.0, 0.0), Vec2::new(-50.0, 0.0)]
+    );
+    assert_eq!(
+        FormationShape::V.offsets(3, 50.0),
+        [Vec2::ZERO, Vec2::new(50.0, 50.0), Vec2::new(-50.0, 50.0)]
+    );
+    let grid = FormationShape::Grid { columns: 3 }.offsets(6, 50.0);
+    assert!(grid.contains(&Vec2::new(-50.0, 0.0)));
+    assert!(grid.contains(&Vec2::new(50.0, 50.0)));
+    for offset in &FormationShape::Ring.offsets(5, 50.0)[1..] {
+        assert!((offset.length() - 50.0).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn formations_are_read_from_the_level_file() {
+    let level: LevelConfig = ron::from_str(
+        "(waves: [Formation((leader: (hp: 100), shape: Grid(columns: 4), count: 8, on_leader_death: Scatter))])",
+    )
+    .unwrap();
+    let WaveConfig::Formation(formation) = &level.waves[0] else {
+        panic!("not a formation");
+    };
+    assert_eq!(formation.shape, FormationShape::Grid { columns: 4 });
+    assert_eq!(formation.count, 8);
+    assert_eq!(formation.on_leader_death, LeaderDeath::Scatter);
+    // The other planes are like the leader when they are left out
+    assert_eq!(formation.member_config().hp, 100);
+}
+
+// The planes left in the arena once the player has shot down the leader of a line of three,
+// which settles right above the player and only fires at the start
+fn survivors(on_leader_death: &str) -> Vec<(Vec2, u32)> {
+    let mut game = HeadlessGame::new(1, 1);
+    let level: LevelConfig = ron::from_str(&format!(
+        "(waves: [Formation((
+            leader: (hp: 10, position: Determinate((0.0, 150.0)), shooting_interval: 1000.0),
+            member: Some((hp: 1000, shooting_interval: 1000.0)),
+            shape: Line,
+            count: 3,
+            spacing: 90.0,
+            on_leader_death: {on_leader_death},
+        ))])"
+    ))
+    .unwrap();
+    game.set_waves(level.waves);
+    game.step(64 * 2);
+    assert_eq!(game.enemy_count(), 2, "the leader is still there");
+    game.step(64 * 2);
+    game.enemies()
+}
+
+#[test]
+fn members_keep_their_places_when_the_leader_is_shot_down() {
+    let mut survivors = survivors("Keep");
+    survivors.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+    assert_eq!(survivors.len(), 2);
+    for ((position, _), expected) in survivors.iter().zip([-90.0, 90.0]) {
+        assert!(
+            position.distance(Vec2::new(expected, 150.0)) < 1.0,
+            "{position} is not where it was in the line"
+        );
+    }
+}
+
+#[test]
+fn members_scatter_when_the_leader_is_shot_down() {
+    let survivors = survivors("Scatter");
+    assert_eq!(survivors.len(), 2);
+    for (position, _) in &survivors {
+        // Away from where the leader was, each to its own side
+        assert!(
+            position.distance(Vec2::new(0.0, 150.0)) > 150.0,
+            "{position} stayed by the leader"
+        );
+        assert!((position.y - 150.0).abs() < 1.0);
+    }
+    assert!(survivors[0].0.x * survivors[1].0.x < 0.0);
+}
+
+#[test]
+fn formations_dive_as_one() {
+    let mut game = HeadlessGame::new(1, 1);
+    let level: LevelConfig = ron::from_str(
+        "(waves: [Formation((
+            leader: (
+                hp: 1000,
+                position: Determinate((0.0, 200.0)),
+                shooting_interval: 1000.0,
+                path: Some(Dive(delay: 0.5, speed: 200.0)),
+            ),
+            shape: Line,
+            count: 3,
+            spacing: 90.0,
+        ))])",
+    )
+    .unwrap();
+    game.set_waves(level.waves);
+    // The formation comes after a second, and is half a second into its dive at the player
+    game.step(64 * 2);
+    let mut planes: Vec<Vec2> = game
+        .enemies()
+        .into_iter()
+        .map(|(position, _)| position)
+        .collect();
+    planes.sort_by(|a, b| a.x.total_cmp(&b.x));
+    assert_eq!(planes.len(), 3);
+    assert!(planes[1].y < 150.0, "the formation hasn't dived");
+    // Each plane is still where it was in the line
+    assert!((planes[0] - planes[1]).distance(Vec2::new(-90.0, 0.0)) < 1.0);
+    assert!((planes[2] - planes[1]).distance(Vec2::new(90.0, 0.0)) < 1.0);
+}
@@ -0,0 +1,440 @@
+//! Gameplay tests that run the levels headlessly with `HeadlessGame`.
+
+use bevy::prelude::{ColorMaterial, KeyCode, Mesh, Vec2};
+use thunder::game::config::WaveConfig;
+use thunder::game::headless::HeadlessGame;
+use thunder::GameState;
+
+#[test]
+fn level_starts_with_full_hp() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.step(1);
+    assert_eq!(game.state(), GameState::Game);
+    assert_eq!(game.score(), 0);
+    assert_eq!(game.wave(), 0);
+    assert_eq!(game.player_hp(), Some(100));
+}
+
+#[test]
+fn missing_level_leads_to_error_screen() {
+    let game = HeadlessGame::new(99, 1);
+    assert_eq!(game.state(), GameState::Error);
+}
+
+#[test]
+fn an_arena_too_small_to_play_in_leads_to_error_screen() {
+    let game = HeadlessGame::with_asset_folder("tests/assets", 1, 1);
+    assert_eq!(game.state(), GameState::Error);
+}
+
+#[test]
+fn player_moves_with_keys() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.step(1);
+    let start = game.player_position().unwrap();
+    game.press(KeyCode::ArrowLeft);
+    game.step(10);
+    game.release(KeyCode::ArrowLeft);
+    let moved = game.player_position().unwrap();
+    assert!(moved.x < start.x);
+    assert_eq!(moved.y, start.y);
+}
+
+#[test]
+fn focusing_halves_the_speed() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.step(1);
+    let start = game.player_position().unwrap();
+    game.press(KeyCode::ArrowLeft);
+    game.step(10);
+    let full_speed = start.x - game.player_position().unwrap().x;
+    game.press(KeyCode::ShiftLeft);
+    game.step(10);
+    let focused = start.x - full_speed - game.player_position().unwrap().x;
+    assert!((focused - full_speed / 2.0).abs() < 0.01);
+}
+
+#[test]
+fn player_stays_inside_the_arena() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.press(KeyCode::KeyA);
+    game.press(KeyCode::KeyS);
+    game.step(64 * 3);
+    let arena = game.arena();
+    let position = game.player_position().unwrap();
+    assert!(position.x > arena.left());
+    assert!(position.y > arena.bottom());
+    assert!(position.x < arena.left() + 60.0);
+    assert!(position.y < arena.bottom() + 60.0);
+}
+
+#[test]
+fn same_seed_and_inputs_give_same_run() {
+    let run = |seed| {
+        let mut game = HeadlessGame::new(2, seed);
+        game.press(KeyCode::KeyD);
+        game.step(200);
+        game.release(KeyCode::KeyD);
+        game.press(KeyCode::KeyW);
+        game.step(400);
+        (game.score(), game.wave(), game.player_hp(), game.state())
+    };
+    assert_eq!(run(7), run(7));
+}
+
+#[test]
+fn shooting_down_the_first_wave() {
+    let mut game = HeadlessGame::new(1, 1);
+    // Fly under the enemy on the right, which shoots straight down
+    game.press(KeyCode::KeyD);
+    game.step(48);
+    game.release(KeyCode::KeyD);
+    game.step(64 * 2);
+    assert_eq!(game.score(), 1);
+    assert_eq!(game.wave(), 0);
+    // Then under the enemy on the left
+    game.press(KeyCode::KeyA);
+    game.step(96);
+    game.release(KeyCode::KeyA);
+    game.step(64 * 2);
+    assert_eq!(game.score(), 2);
+    assert_eq!(game.wave(), 1);
+    assert_eq!(game.player_hp(), Some(100));
+    // The enemy of the next wave shoots back at the middle of the arena
+    game.press(KeyCode::KeyD);
+    game.step(48);
+    game.release(KeyCode::KeyD);
+    game.step(64 * 6);
+    assert_eq!(game.score(), 3);
+    assert_eq!(game.wave(), 2);
+    assert!(game.player_hp().unwrap() < 100);
+    assert_eq!(game.state(), GameState::Game);
+}
+
+#[test]
+fn shooting_does_not_add_assets() {
+    let mut game = HeadlessGame::new(1, 1);
+    // By then both the player and the enemies have shot
+    game.step(64 * 3);
+    let meshes = game.asset_count::<Mesh>();
+    let materials = game.asset_count::<ColorMaterial>();
+    game.step(64 * 4);
+    assert_eq!(game.asset_count::<Mesh>(), meshes);
+    assert_eq!(game.asset_count::<ColorMaterial>(), materials);
+}
+
+#[test]
+fn bullets_are_capped() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_max_bullets(3);
+    for _ in 0..64 * 4 {
+        game.step(1);
+        assert!(game.bullet_count() <= 3);
+    }
+}
+
+#[test]
+fn near_misses_are_grazes() {
+    let mut game = HeadlessGame::new(1, 1);
+    // Fly next to the enemy on the right, so that its bullets pass close by,
+    // but far enough for the bullets of both to miss
+    game.press(KeyCode::KeyD);
+    game.step(40);
+    game.release(KeyCode::KeyD);
+    game.step(64 * 3);
+    assert_eq!(game.graze(), 0);
+    game.step(64);
+    assert_eq!(game.graze(), 1);
+    assert_eq!(game.player_hp(), Some(100));
+}
+
+#[test]
+fn hits_are_followed_by_invulnerability() {
+    let mut game = HeadlessGame::new(1, 1);
+    assert!(!game.player_invulnerable());
+    sit_under_the_second_wave(&mut game);
+    let mut hp = game.player_hp().unwrap();
+    let mut last_hit: Option<u32> = None;
+    for tick in 0..64 * 6 {
+        game.step(1);
+        let new_hp = game.player_hp().unwrap();
+        if new_hp < hp {
+            assert!(game.player_invulnerable());
+            if let Some(last_hit) = last_hit {
+                assert!(tick - last_hit >= 64);
+            }
+            last_hit = Some(tick);
+        }
+        hp = new_hp;
+    }
+    assert!(last_hit.is_some());
+}
+
+// Shoot down the first wave of level 1, and go back to the middle of the arena,
+// where the enemy of the second wave shoots
+fn sit_under_the_second_wave(game: &mut HeadlessGame) {
+    game.press(KeyCode::KeyD);
+    game.step(48);
+    game.release(KeyCode::KeyD);
+    game.step(64 * 2);
+    game.press(KeyCode::KeyA);
+    game.step(96);
+    game.release(KeyCode::KeyA);
+    game.step(64 * 2);
+    game.press(KeyCode::KeyD);
+    game.step(48);
+    game.release(KeyCode::KeyD);
+}
+
+#[test]
+fn running_out_of_hp_takes_a_life() {
+    let mut game = HeadlessGame::new(1, 1);
+    assert_eq!(game.lives(), 3);
+    sit_under_the_second_wave(&mut game);
+    game.set_player_hp(1);
+    for _ in 0..64 * 6 {
+        game.step(1);
+        if game.lives() < 3 {
+            break;
+        }
+    }
+    assert_eq!(game.lives(), 2);
+    assert_eq!(game.player_hp(), Some(100));
+    assert!(game.player_invulnerable());
+    let arena = game.arena();
+    let position = game.player_position().unwrap();
+    assert_eq!(position.x, 0.0);
+    assert!(position.y < arena.bottom() + 50.0);
+    assert_eq!(game.state(), GameState::Game);
+}
+
+#[test]
+fn the_last_life_loses_the_level() {
+    let mut game = HeadlessGame::new(1, 1);
+    sit_under_the_second_wave(&mut game);
+    game.set_lives(1);
+    game.set_player_hp(1);
+    game.step(64 * 6);
+    assert_eq!(game.lives(), 0);
+    assert_eq!(game.player_hp(), None);
+    assert_eq!(game.state(), GameState::Lose);
+}
+
+#[test]
+fn touching_an_enemy_hurts() {
+    let mut game = HeadlessGame::new(1, 1);
+    // The enemies of the level don't ram, so touching them does nothing
+    game.step(64 * 2);
+    let player_position = game.player_position().unwrap();
+    game.move_enemies_to(player_position);
+    game.step(1);
+    assert_eq!(game.player_hp(), Some(100));
+
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[Detailed([(hp: 1000, position: Determinate((0.0, 200.0)), contact_damage: 20)])]",
+    ));
+    // Let the rammer come in, then bring it onto the player
+    game.step(64 * 2);
+    let player_position = game.player_position().unwrap();
+    game.move_enemies_to(player_position);
+    game.step(1);
+    assert_eq!(game.player_hp(), Some(80));
+    assert!(game.player_invulnerable());
+    // The enemy stays on the plane, which is hurt again once its invulnerability is over
+    game.step(64);
+    assert_eq!(game.player_hp(), Some(60));
+}
+
+#[test]
+fn escaped_enemies_do_not_hold_up_the_next_wave() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.step(64 * 2);
+    assert_eq!(game.wave(), 0);
+    // Carry the first wave off, past the top wall
+    let arena = game.arena();
+    game.move_enemies_to(Vec2::new(0.0, arena.top() + 100.0));
+    game.step(1);
+    assert_eq!(game.escaped(), 2);
+    assert_eq!(game.score(), 0);
+    assert_eq!(game.wave(), 1);
+}
+
+fn waves(level_file: &str) -> Vec<WaveConfig> {
+    ron::from_str(level_file).unwrap()
+}
+
+#[test]
+fn enemies_come_after_their_spawn_delay_and_reinforcements_on_time() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[Scripted((
+            enemies: Detailed([
+                (position: Determinate((-225.0, 150.0))),
+                (position: Determinate((225.0, 150.0)), spawn_delay: 1.0),
+            ]),
+            reinforcements: [(
+                trigger: After(2.0),
+                enemies: Duplicate((position: Determinate((225.0, 100.0))), 2),
+            )],
+        ))]",
+    ));
+    // The wave starts after a second
+    game.step(64 + 8);
+    assert_eq!(game.enemy_count(), 1);
+    game.step(64);
+    assert_eq!(game.enemy_count(), 2);
+    game.step(64);
+    assert_eq!(game.enemy_count(), 4);
+    assert_eq!(game.wave(), 0);
+}
+
+#[test]
+fn hurting_the_leader_calls_in_reinforcements() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[Scripted((
+            enemies: Detailed([
+                (position: Determinate((0.0, 150.0)), hp: 30),
+                (position: Determinate((225.0, 150.0))),
+            ]),
+            reinforcements: [(
+                trigger: LeaderHpBelow(0.5),
+                enemies: Duplicate((position: Determinate((-225.0, 150.0))), 2),
+            )],
+        ))]",
+    ));
+    game.step(64 + 8);
+    assert_eq!(game.enemy_count(), 2);
+    // The player shoots the leader right above it down
+    game.step(64 * 4);
+    assert_eq!(game.score(), 1);
+    assert_eq!(game.enemy_count(), 3);
+    assert_eq!(game.wave(), 0);
+}
+
+#[test]
+fn cleared_waves_wait_for_their_reinforcements() {
+    let run = |cancel_on_clear: bool| {
+        let mut game = HeadlessGame::new(1, 1);
+        game.set_waves(waves(&format!(
+            "[
+                Scripted((
+                    enemies: Duplicate((position: Determinate((0.0, 150.0)), hp: 10), 1),
+                    reinforcements: [(
+                        trigger: After(4.0),
+                        enemies: Duplicate((position: Determinate((225.0, 150.0))), 1),
+                        cancel_on_clear: {cancel_on_clear},
+                    )],
+                )),
+                Duplicate((position: Determinate((-225.0, 150.0))), 1),
+            ]"
+        )));
+        // The player shoots the enemy right above it down well before the reinforcement
+        game.step(64 * 3);
+        assert_eq!(game.score(), 1);
+        let wave_after_clearing = game.wave();
+        game.step(64 * 3);
+        (wave_after_clearing, game.wave(), game.enemy_count())
+    };
+    assert_eq!(run(false), (0, 0, 1));
+    // The next wave comes after its own interval, instead of the reinforcement
+    assert_eq!(run(true), (1, 1, 1));
+}
+
+#[test]
+fn the_time_limit_brings_the_next_wave() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[
+            Scripted((
+                enemies: Duplicate((position: Determinate((225.0, 150.0))), 1),
+                time_limit: Some(1.0),
+            )),
+            Duplicate((position: Determinate((-225.0, 150.0))), 1),
+        ]",
+    ));
+    game.step(64 + 8);
+    assert_eq!(game.wave(), 0);
+    game.step(64);
+    assert_eq!(game.wave(), 1);
+    // The enemy of the first wave is still there
+    assert_eq!(game.enemy_count(), 1);
+    assert_eq!(game.score(), 0);
+}
+
+#[test]
+fn ring_patterns_fire_every_bullet_at_once() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[Duplicate((
+            position: Determinate((225.0, 150.0)),
+            bullet_pattern: Ring(count: 12),
+            shooting_interval: 1.0,
+        ), 1)]",
+    ));
+    let mut most_in_a_tick = 0;
+    let mut bullets = game.bullet_count();
+    for _ in 0..64 * 3 {
+        game.step(1);
+        let now = game.bullet_count();
+        most_in_a_tick = most_in_a_tick.max(now.saturating_sub(bullets));
+        bullets = now;
+    }
+    // The player may shoot in the same tick
+    assert!((12..=13).contains(&most_in_a_tick));
+}
+
+#[test]
+fn the_last_wave_has_to_be_cleared_despite_its_time_limit() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.set_waves(waves(
+        "[Scripted((
+            enemies: Duplicate((position: Determinate((225.0, 150.0))), 1),
+            time_limit: Some(1.0),
+        ))]",
+    ));
+    game.step(64 * 4);
+    assert_eq!(game.wave(), 0);
+    assert_eq!(game.enemy_count(), 1);
+    assert_eq!(game.state(), GameState::Game);
+}
+
+#[test]
+fn editing_the_level_changes_the_enemies_still_to_come() {
+    let mut game = HeadlessGame::new(1, 1);
+    let wave = |delayed_enemy: &str| {
+        waves(&format!(
+            "[Detailed([
+                (position: Determinate((225.0, 150.0))),
+                ({delayed_enemy}, spawn_delay: 2.0),
+            ])]"
+        ))
+    };
+    game.set_waves(wave("position: Determinate((-225.0, 150.0))"));
+    game.step(64 + 8);
+    assert_eq!(game.enemy_count(), 1);
+    game.set_waves(wave("position: Determinate((-100.0, 150.0)), hp: 55"));
+    game.step(64 * 2);
+    // The enemy that had come stays, and the other comes as the file says now
+    let enemies = game.enemies();
+    assert_eq!(enemies.len(), 2);
+    assert!(enemies
+        .iter()
+        .any(|(position, hp)| position.x == -100.0 && *hp == 55));
+}
+
+#[test]
+fn laser_stars_are_caught_by_the_wings() {
+    let mut game = HeadlessGame::new(1, 1);
+    game.step(1);
+    let player_position = game.player_position().unwrap();
+    // On the wing, well away from the hitbox in the middle of the plane
+    game.spawn_laser_star(player_position + Vec2::new(30.0, 0.0));
+    game.step(1);
+    assert_eq!(game.laser_star_count(), 0);
+    // Clear of the plane
+    game.spawn_laser_star(player_position + Vec2::new(60.0, 0.0));
+    game.step(1);
+    assert_eq!(game.laser_star_count(), 1);
+}
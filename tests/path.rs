@@ -0,0 +1,105 @@
+//! Tests of the flight paths of the enemy planes.
+
+use bevy::prelude::Vec2;
+use thunder::game::config::EnemyConfig;
+use thunder::game::path::{FlightPath, PathConfig};
+
+const START: Vec2 = Vec2::new(100.0, 200.0);
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    assert!(
+        actual.distance(expected) < 1e-3,
+        "{actual} is not {expected}"
+    );
+}
+
+#[test]
+fn splines_go_from_the_start_to_their_last_point() {
+    let points = vec![Vec2::new(0.0, -100.0), Vec2::new(200.0, -100.0)];
+    for path in [
+        PathConfig::Bezier {
+            points: points.clone(),
+            time: 2.0,
+        },
+        PathConfig::CatmullRom {
+            points: points.clone(),
+            time: 2.0,
+        },
+    ] {
+        let flight_path = FlightPath::new(path, START);
+        assert_near(flight_path.position_at(0.0), START);
+        assert_near(flight_path.position_at(2.0), START + points[1]);
+        // The plane stays at the end of the spline
+        assert_near(flight_path.position_at(5.0), START + points[1]);
+    }
+    // Catmull-Rom curves also go through the points on the way
+    let flight_path = FlightPath::new(PathConfig::CatmullRom { points, time: 2.0 }, START);
+    assert_near(flight_path.position_at(1.0), START + Vec2::new(0.0, -100.0));
+}
+
+#[test]
+fn sine_and_circle_come_back_after_a_period() {
+    let flight_path = FlightPath::new(
+        PathConfig::Sine {
+            velocity: Vec2::new(0.0, -50.0),
+            amplitude: 30.0,
+            period: 2.0,
+        },
+        START,
+    );
+    assert_near(flight_path.position_at(0.5), START + Vec2::new(30.0, -25.0));
+    assert_near(flight_path.position_at(2.0), START + Vec2::new(0.0, -100.0));
+
+    let flight_path = FlightPath::new(
+        PathConfig::Circle {
+            center: Vec2::new(0.0, -50.0),
+            period: 4.0,
+        },
+        START,
+    );
+    assert_near(flight_path.position_at(2.0), START + Vec2::new(0.0, -100.0));
+    assert_near(flight_path.position_at(4.0), START);
+}
+
+#[test]
+fn dives_aim_at_the_player_once_they_start() {
+    let mut flight_path = FlightPath::new(
+        PathConfig::Dive {
+            delay: 1.0,
+            speed: 100.0,
+        },
+        START,
+    );
+    let player = START + Vec2::new(300.0, -400.0);
+    let mut position = START;
+    let delta = 1.0 / 64.0;
+    // The plane waits, then flies a straight line at where the player was when the dive started
+    for tick in 0..128 {
+        let player = match tick < 80 {
+            true => player,
+            false => Vec2::ZERO,
+        };
+        position += flight_path.velocity(position, Some(player), delta) * delta;
+        if tick < 63 {
+            assert_near(position, START);
+        }
+    }
+    assert!(position.distance(START) > 90.0);
+    let heading = (position - START).normalize();
+    assert_near(heading, Vec2::new(0.6, -0.8));
+}
+
+#[test]
+fn paths_are_read_from_the_level_file() {
+    let enemy: EnemyConfig =
+        ron::from_str("(path: Some(Circle(center: (0.0, -60.0), period: 3.0)))").unwrap();
+    assert_eq!(
+        enemy.path,
+        Some(PathConfig::Circle {
+            center: Vec2::new(0.0, -60.0),
+            period: 3.0,
+        })
+    );
+    let enemy: EnemyConfig = ron::from_str("(hp: 100)").unwrap();
+    assert_eq!(enemy.path, None);
+}
@@ -0,0 +1,92 @@
+//! Tests of the bullet patterns of the guns.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+use std::time::Duration;
+
+use thunder::game::config::EnemyConfig;
+use thunder::game::pattern::{BulletPattern, Emitter};
+
+const TICK: Duration = Duration::from_micros(15625);
+
+fn assert_angles(actual: Vec<f32>, expected: &[f32]) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{actual:?} is not {expected:?}"
+    );
+    for (actual_angle, expected_angle) in actual.iter().zip(expected) {
+        assert!(
+            (actual_angle - expected_angle).abs() < 1e-4,
+            "{actual:?} is not {expected:?}"
+        );
+    }
+}
+
+#[test]
+fn spreads_fan_out_and_rings_go_all_around() {
+    let spread = BulletPattern::Spread {
+        count: 3,
+        angle: FRAC_PI_2,
+    };
+    assert_angles(spread.offsets(0), &[-FRAC_PI_2 / 2.0, 0.0, FRAC_PI_2 / 2.0]);
+    let ring = BulletPattern::Ring { count: 4 };
+    assert_angles(ring.offsets(0), &[0.0, FRAC_PI_2, PI, 3.0 * FRAC_PI_2]);
+    // A ring looks the same on every shot, a spiral turns
+    assert_eq!(ring.offsets(5), ring.offsets(0));
+    let spiral = BulletPattern::Spiral {
+        arms: 2,
+        rotation: 0.25,
+    };
+    assert_angles(spiral.offsets(0), &[0.0, PI]);
+    assert_angles(spiral.offsets(2), &[0.5, PI + 0.5]);
+}
+
+#[test]
+fn bursts_fire_one_bullet_at_a_time() {
+    let mut emitter = Emitter::new(BulletPattern::Burst {
+        count: 3,
+        interval: 0.125,
+    });
+    let mut fired = Vec::new();
+    for tick in 0..32 {
+        fired.push(emitter.tick(tick == 0, TICK).len());
+    }
+    // The first bullet comes with the shot, the others 8 ticks apart
+    let ticks: Vec<usize> = (0..fired.len()).filter(|tick| fired[*tick] > 0).collect();
+    assert_eq!(ticks, vec![0, 8, 16]);
+    assert!(fired.iter().all(|bullets| *bullets <= 1));
+}
+
+#[test]
+fn alternating_patterns_take_turns() {
+    let mut emitter = Emitter::new(BulletPattern::Alternate(vec![
+        BulletPattern::Ring { count: 6 },
+        BulletPattern::Single,
+        BulletPattern::Spiral {
+            arms: 2,
+            rotation: 1.0,
+        },
+    ]));
+    let shots: Vec<Vec<f32>> = (0..6).map(|_| emitter.tick(true, TICK)).collect();
+    assert_eq!(shots[0].len(), 6);
+    assert_angles(shots[1].clone(), &[0.0]);
+    assert_angles(shots[2].clone(), &[0.0, PI]);
+    assert_eq!(shots[3].len(), 6);
+    // The spiral turns once for each of its own shots
+    assert_angles(shots[5].clone(), &[1.0, PI + 1.0]);
+}
+
+#[test]
+fn patterns_are_read_from_the_level_file() {
+    let enemy: EnemyConfig =
+        ron::from_str("(bullet_pattern: Spread(count: 5, angle: 1.0))").unwrap();
+    assert_eq!(
+        enemy.bullet_pattern,
+        BulletPattern::Spread {
+            count: 5,
+            angle: 1.0
+        }
+    );
+    let enemy: EnemyConfig = ron::from_str("(hp: 100)").unwrap();
+    assert_eq!(enemy.bullet_pattern, BulletPattern::Single);
+}
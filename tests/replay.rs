@@ -0,0 +1,113 @@
+//! Tests of the recorded runs listed in the replays menu.
+
+use bevy::prelude::{KeyCode, Vec2};
+use thunder::game::headless::HeadlessGame;
+use thunder::game::replay::{Outcome, Replay, ReplaySummary};
+use thunder::GameState;
+
+#[test]
+fn summaries_are_read_from_full_replays() {
+    let path = std::env::temp_dir().join("thunder_summary_test.replay.ron");
+    std::fs::write(
+        &path,
+        "(level: 3, seed: 42, score: 1200, outcome: Win, inputs: [\
+            (input: (direction: (1.0, 0.0), laser: false, focus: true), steps: 64),\
+            (input: (direction: (0.0, 0.0), laser: true), steps: 12),\
+        ])",
+    )
+    .unwrap();
+    let summary = ReplaySummary::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(summary.level, 3);
+    assert_eq!(summary.score, 1200);
+    assert_eq!(summary.outcome, Outcome::Win);
+}
+
+#[test]
+fn older_replays_have_a_summary_too() {
+    let path = std::env::temp_dir().join("thunder_old_summary_test.replay.ron");
+    std::fs::write(&path, "(level: 1, seed: 7, inputs: [])").unwrap();
+    let summary = ReplaySummary::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(summary.level, 1);
+    assert_eq!(summary.score, 0);
+    assert_eq!(summary.outcome, Outcome::Quit);
+}
+
+#[test]
+fn a_replay_whose_level_fails_to_load_gives_the_keyboard_back() {
+    let replay: Replay = ron::from_str("(level: 99, seed: 5, inputs: [])").unwrap();
+    let mut game = HeadlessGame::replaying(replay);
+    assert_eq!(game.state(), GameState::Error);
+    // The next game is played with the keyboard, and with a seed of its own
+    assert!(!game.is_replaying());
+    game.return_to_menu();
+    assert!(!game.is_replaying());
+    assert_eq!(game.playback_speed(), 1.0);
+}
+
+#[test]
+fn leaving_a_replay_brings_the_time_back_to_normal() {
+    let replay: Replay = ron::from_str("(level: 1, seed: 5, inputs: [])").unwrap();
+    let mut game = HeadlessGame::replaying(replay);
+    assert!(game.is_replaying());
+    game.press(KeyCode::Digit4);
+    game.step(1);
+    assert_eq!(game.state(), GameState::Game);
+    assert_eq!(game.playback_speed(), 4.0);
+    game.return_to_menu();
+    assert!(!game.is_replaying());
+    assert_eq!(game.playback_speed(), 1.0);
+}
+
+// What can be seen of the game after a tick
+type Snapshot = (Option<Vec2>, u32, u32, Option<u32>, Vec<(Vec2, u32)>);
+
+fn snapshot(game: &mut HeadlessGame) -> Snapshot {
+    (
+        game.player_position(),
+        game.score(),
+        game.wave(),
+        game.player_hp(),
+        game.enemies(),
+    )
+}
+
+#[test]
+fn a_recorded_run_plays_back_the_same_on_every_tick() {
+    let mut game = HeadlessGame::new(2, 11);
+    let mut recorded = vec![snapshot(&mut game)];
+    for (key, ticks) in [
+        (KeyCode::KeyD, 40),
+        (KeyCode::KeyW, 30),
+        (KeyCode::KeyA, 90),
+        (KeyCode::KeyL, 120),
+        (KeyCode::KeyS, 20),
+        (KeyCode::KeyD, 60),
+    ] {
+        game.press(key);
+        for _ in 0..ticks {
+            game.step(1);
+            recorded.push(snapshot(&mut game));
+        }
+        game.release(key);
+        for _ in 0..64 {
+            game.step(1);
+            recorded.push(snapshot(&mut game));
+        }
+    }
+    // Something happened worth checking
+    let (_, score, _, hp, enemies) = recorded.last().unwrap();
+    assert!(*score > 0 || *hp != Some(100) || !enemies.is_empty());
+
+    let mut replay = HeadlessGame::replaying(game.recording());
+    assert_eq!(snapshot(&mut replay), recorded[0]);
+    for (tick, expected) in recorded.iter().enumerate().skip(1) {
+        replay.step(1);
+        assert_eq!(
+            &snapshot(&mut replay),
+            expected,
+            "the replay went astray on tick {tick}"
+        );
+    }
+}